@@ -1,13 +1,18 @@
 use std::error::Error;
 
 use crate::{
+    core::Transaction,
     crypto::keystore::{KDFType, KeyStore},
     keytools::{get_address_from_public_key, get_public_key_from_private_key},
+    transaction::sign_transaction,
+    util::normalize_address,
 };
 
+/// An account is either backed by a private key, or watch-only: known by its address and
+/// optionally its public key, but unable to sign.
 pub struct Account {
-    private_key: Vec<u8>,
-    public_key: Vec<u8>,
+    private_key: Option<Vec<u8>>,
+    public_key: Option<Vec<u8>>,
     address: String,
 }
 
@@ -16,9 +21,49 @@ impl Account {
         let public_key = get_public_key_from_private_key(&private_key, true).unwrap();
         let address = get_address_from_public_key(&public_key).unwrap();
         Self {
-            private_key,
+            private_key: Some(private_key),
+            public_key: Some(public_key),
+            address,
+        }
+    }
+
+    /// Creates a watch-only account. If `public_key` is given it must belong to `address`.
+    pub fn watch_only(address: &str, public_key: Option<Vec<u8>>) -> Result<Self, Box<dyn Error>> {
+        let address = normalize_address(address)?;
+
+        if let Some(public_key) = &public_key {
+            if get_address_from_public_key(public_key)? != address {
+                return Err(format!("public key does not belong to {}", address).into());
+            }
+        }
+
+        Ok(Self {
+            private_key: None,
             public_key,
             address,
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub(crate) fn private_key(&self) -> Option<&[u8]> {
+        self.private_key.as_deref()
+    }
+
+    pub fn public_key(&self) -> Option<&[u8]> {
+        self.public_key.as_deref()
+    }
+
+    pub fn is_watch_only(&self) -> bool {
+        self.private_key.is_none()
+    }
+
+    pub fn sign_transaction(&self, tx: &mut Transaction) -> Result<(), Box<dyn Error>> {
+        match &self.private_key {
+            Some(private_key) => sign_transaction(tx, private_key),
+            None => Err(format!("{} is a watch-only account", self.address).into()),
         }
     }
 }
@@ -53,11 +98,35 @@ mod tests {
         .is_ok());
     }
 
+    #[test]
+    fn test_watch_only() {
+        let public_key =
+            hex::decode("0246e7178dc8253201101e18fd6f6eb9972451d121fc57aa2a06dd5c111e58dc6a")
+                .unwrap();
+
+        let account = Account::watch_only(
+            "0x9BFEC715A6BD658FCB62B0F8CC9BFA2ADE71434A",
+            Some(public_key.clone()),
+        )
+        .unwrap();
+        assert!(account.is_watch_only());
+        assert_eq!(
+            account.address(),
+            "9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a"
+        );
+        assert_eq!(account.public_key(), Some(&public_key[..]));
+
+        assert!(
+            Account::watch_only("b5c2cdd79c37209c3cb59e04b7c4062a8f5d5271", Some(public_key))
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_from_file() {
         let account = from_file(FILE, "xiaohuo").unwrap();
         assert_eq!(
-            hex::encode(account.private_key),
+            hex::encode(account.private_key.unwrap()),
             "24180e6b0c3021aedb8f5a86f75276ee6fc7ff46e67e98e716728326102e91c9"
         )
     }
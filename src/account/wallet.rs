@@ -1,66 +1,755 @@
-use std::{collections::HashMap, rc::Rc};
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    path::Path,
+    sync::{Arc, RwLock},
+};
 
-use crate::keytools::get_address_from_private_key;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    core::Transaction,
+    crypto::keystore::{Crypto, KDFType, KeyStore},
+    keytools::{parse_private_key, KeyError},
+    util::normalize_address,
+};
 
 use super::account::Account;
 
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum WalletError {
+    #[error(transparent)]
+    Key(#[from] KeyError),
+    #[error("invalid address {0}")]
+    InvalidAddress(String),
+    #[error("no account for address {0}")]
+    AccountNotFound(String),
+    #[error("an account for address {0} already exists")]
+    AccountExists(String),
+    #[error("invalid public key for address {0}")]
+    InvalidPublicKey(String),
+    #[error("public key of address {0} is unknown")]
+    MissingPublicKey(String),
+    #[error("{0} is a watch-only account and can not sign")]
+    WatchOnly(String),
+    #[error("wallet has no default account")]
+    NoDefaultAccount,
+    #[error("failed to sign transaction: {0}")]
+    Signing(String),
+    #[error("unsupported wallet file version {0}")]
+    UnsupportedVersion(u32),
+    #[error("invalid wallet file: {0}")]
+    InvalidFile(String),
+    #[error("failed to encrypt or decrypt wallet: {0}")]
+    Crypto(String),
+    #[error("{0}")]
+    Io(String),
+}
+
+pub type Result<T> = std::result::Result<T, WalletError>;
+
+/// A set of accounts that can be shared between threads and tokio tasks. Cloning a `Wallet` is
+/// cheap and every clone refers to the same accounts.
+///
+/// Methods taking an address accept bech32, checksum and plain hex addresses alike.
+#[derive(Clone, Default)]
 pub struct Wallet {
-    accounts: HashMap<String, Rc<Account>>,
-    default_account: Option<Rc<Account>>,
+    inner: Arc<RwLock<Accounts>>,
+}
+
+/// Accounts keyed by lowercase hex address without `0x`.
+#[derive(Default)]
+struct Accounts {
+    accounts: HashMap<String, Arc<Account>>,
+    labels: HashMap<String, String>,
+    default_account: Option<String>,
+}
+
+/// Version 2 added watch-only accounts.
+const WALLET_FILE_VERSION: u32 = 2;
+
+/// The encrypted wallet file. `version` is bumped whenever the encrypted contents change in a
+/// way older versions can not read.
+#[derive(Serialize, Deserialize)]
+struct WalletFile {
+    version: u32,
+    crypto: Crypto,
+}
+
+/// The plain text contents of a wallet file. New fields must be optional so that files written
+/// by older versions still load.
+#[derive(Serialize, Deserialize)]
+struct WalletContents {
+    #[serde(default)]
+    accounts: Vec<StoredAccount>,
+    #[serde(default)]
+    default_account: Option<String>,
+}
+
+/// Either `private_key`, or `address` and optionally `public_key` for watch-only accounts.
+#[derive(Serialize, Deserialize)]
+struct StoredAccount {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    private_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    public_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
 }
 
 impl Wallet {
     pub fn new() -> Self {
-        Self {
-            accounts: HashMap::new(),
-            default_account: None,
+        Self::default()
+    }
+
+    /// Adds an account from a private key in any format accepted by
+    /// [`parse_private_key`](crate::keytools::parse_private_key). The first account added
+    /// becomes the default account.
+    pub fn add_by_private_key(&self, private_key: &str) -> Result<()> {
+        self.insert(Account::new(parse_private_key(private_key)?));
+        Ok(())
+    }
+
+    /// Adds a watch-only account, which can be listed, labelled and used to build transactions,
+    /// but not to sign them. A public key is required to build transactions. Watch-only accounts
+    /// never become the default account.
+    pub fn add_watch_only(&self, address: &str, public_key: Option<&str>) -> Result<()> {
+        let address = normalize(address)?;
+
+        let public_key = public_key
+            .map(|public_key| hex::decode(public_key.trim_start_matches("0x")))
+            .transpose()
+            .map_err(|_| WalletError::InvalidPublicKey(address.clone()))?;
+        let account = Account::watch_only(&address, public_key)
+            .map_err(|_| WalletError::InvalidPublicKey(address.clone()))?;
+
+        let mut inner = self.inner.write().unwrap();
+        if matches!(inner.accounts.get(&address), Some(existing) if !existing.is_watch_only()) {
+            return Err(WalletError::AccountExists(address));
+        }
+        inner.accounts.insert(address, Arc::new(account));
+
+        Ok(())
+    }
+
+    /// Removes an account. If it was the default account, the wallet is left without one.
+    pub fn remove_account(&self, address: &str) -> Result<Arc<Account>> {
+        let address = normalize(address)?;
+
+        let mut inner = self.inner.write().unwrap();
+        let account = inner
+            .accounts
+            .remove(&address)
+            .ok_or_else(|| WalletError::AccountNotFound(address.clone()))?;
+
+        inner.labels.remove(&address);
+        if inner.default_account.as_ref() == Some(&address) {
+            inner.default_account = None;
+        }
+
+        Ok(account)
+    }
+
+    pub fn get_account(&self, address: &str) -> Result<Arc<Account>> {
+        let address = normalize(address)?;
+
+        self.inner
+            .read()
+            .unwrap()
+            .accounts
+            .get(&address)
+            .cloned()
+            .ok_or(WalletError::AccountNotFound(address))
+    }
+
+    pub fn contains(&self, address: &str) -> bool {
+        self.get_account(address).is_ok()
+    }
+
+    /// Returns all accounts, ordered by address.
+    pub fn accounts(&self) -> Vec<Arc<Account>> {
+        let mut accounts = self
+            .inner
+            .read()
+            .unwrap()
+            .accounts
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        accounts.sort_by(|a, b| a.address().cmp(b.address()));
+        accounts
+    }
+
+    pub fn set_label(&self, address: &str, label: &str) -> Result<()> {
+        let address = normalize(address)?;
+
+        let mut inner = self.inner.write().unwrap();
+        if !inner.accounts.contains_key(&address) {
+            return Err(WalletError::AccountNotFound(address));
+        }
+
+        inner.labels.insert(address, label.to_string());
+
+        Ok(())
+    }
+
+    pub fn label(&self, address: &str) -> Option<String> {
+        let address = normalize(address).ok()?;
+        self.inner.read().unwrap().labels.get(&address).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap().accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn default_account(&self) -> Option<Arc<Account>> {
+        let inner = self.inner.read().unwrap();
+        inner
+            .default_account
+            .as_ref()
+            .and_then(|address| inner.accounts.get(address))
+            .cloned()
+    }
+
+    /// Makes the account for `address` the one [`sign`](Self::sign) uses. It can not be a
+    /// watch-only account.
+    pub fn set_default_account(&self, address: &str) -> Result<()> {
+        let address = normalize(address)?;
+
+        let mut inner = self.inner.write().unwrap();
+        match inner.accounts.get(&address) {
+            None => return Err(WalletError::AccountNotFound(address)),
+            Some(account) if account.is_watch_only() => {
+                return Err(WalletError::WatchOnly(address))
+            }
+            Some(_) => {}
         }
+
+        inner.default_account = Some(address);
+
+        Ok(())
+    }
+
+    /// Sets `sender_pub_key` of `tx` to the public key of the account for `address`, which is
+    /// all that is needed before signing. For watch-only accounts the prepared transaction can
+    /// be handed to an offline signer, which completes it with
+    /// [`sign_transaction`](crate::transaction::sign_transaction).
+    pub fn prepare_transaction(&self, address: &str, tx: &mut Transaction) -> Result<()> {
+        let account = self.get_account(address)?;
+        let public_key = account
+            .public_key()
+            .ok_or_else(|| WalletError::MissingPublicKey(account.address().to_string()))?;
+
+        tx.sender_pub_key = hex::encode(public_key);
+
+        Ok(())
     }
 
-    pub fn add_by_private_key(&mut self, private_key: &str) {
-        let prik = hex::decode(private_key).unwrap();
-        let account = Rc::new(Account::new(prik.clone()));
-        let address = get_address_from_private_key(&prik).unwrap().to_uppercase();
-        self.accounts.insert(address, account.clone());
+    /// Signs `tx` with the default account. The wallet is not locked while signing.
+    pub fn sign(&self, tx: &mut Transaction) -> Result<()> {
+        let account = self
+            .default_account()
+            .ok_or(WalletError::NoDefaultAccount)?;
+
+        sign_with_account(&account, tx)
+    }
 
-        if self.default_account.is_none() {
-            self.default_account = Some(account.clone());
+    /// Signs `tx` with the account for `address`.
+    pub fn sign_with(&self, address: &str, tx: &mut Transaction) -> Result<()> {
+        let account = self.get_account(address)?;
+        sign_with_account(&account, tx)
+    }
+
+    /// Encrypts all accounts, their labels and the default account into a single JSON
+    /// document, using the same key derivation and cipher as [`KeyStore`].
+    pub fn to_file(&self, passphrase: &str, t: KDFType) -> Result<String> {
+        let contents = {
+            let inner = self.inner.read().unwrap();
+            let mut accounts = inner.accounts.iter().collect::<Vec<_>>();
+            accounts.sort_by(|a, b| a.0.cmp(b.0));
+
+            WalletContents {
+                accounts: accounts
+                    .into_iter()
+                    .map(|(address, account)| match account.private_key() {
+                        Some(private_key) => StoredAccount {
+                            private_key: Some(hex::encode(private_key)),
+                            address: None,
+                            public_key: None,
+                            label: inner.labels.get(address).cloned(),
+                        },
+                        None => StoredAccount {
+                            private_key: None,
+                            address: Some(address.clone()),
+                            public_key: account.public_key().map(hex::encode),
+                            label: inner.labels.get(address).cloned(),
+                        },
+                    })
+                    .collect(),
+                default_account: inner.default_account.clone(),
+            }
+        };
+
+        let crypto = KeyStore::default()
+            .encrypt(
+                &serde_json::to_vec(&contents).unwrap(),
+                passphrase.as_bytes(),
+                t,
+            )
+            .map_err(|err| WalletError::Crypto(err.to_string()))?;
+
+        Ok(serde_json::to_string(&WalletFile {
+            version: WALLET_FILE_VERSION,
+            crypto,
+        })
+        .unwrap())
+    }
+
+    pub fn from_file(file: &str, passphrase: &str) -> Result<Self> {
+        let file = serde_json::from_str::<WalletFile>(file)
+            .map_err(|err| WalletError::InvalidFile(err.to_string()))?;
+
+        if file.version == 0 || file.version > WALLET_FILE_VERSION {
+            return Err(WalletError::UnsupportedVersion(file.version));
         }
+
+        let contents = KeyStore::default()
+            .decrypt(&file.crypto, passphrase.as_bytes())
+            .map_err(|err| WalletError::Crypto(err.to_string()))?;
+        let contents = serde_json::from_slice::<WalletContents>(&contents)
+            .map_err(|err| WalletError::InvalidFile(err.to_string()))?;
+
+        let wallet = Self::new();
+        for account in contents.accounts {
+            let address = match (account.private_key, account.address) {
+                (Some(private_key), _) => {
+                    wallet.insert(Account::new(parse_private_key(&private_key)?))
+                }
+                (None, Some(address)) => {
+                    wallet.add_watch_only(&address, account.public_key.as_deref())?;
+                    normalize(&address)?
+                }
+                (None, None) => {
+                    return Err(WalletError::InvalidFile(
+                        "account without private key or address".to_string(),
+                    ))
+                }
+            };
+
+            if let Some(label) = account.label {
+                wallet.set_label(&address, &label)?;
+            }
+        }
+
+        wallet.inner.write().unwrap().default_account = None;
+        if let Some(address) = contents.default_account {
+            // Earlier versions could make a watch-only account the default, such files load
+            // without a default account.
+            match wallet.set_default_account(&address) {
+                Ok(()) | Err(WalletError::WatchOnly(_)) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(wallet)
     }
+
+    /// Writes the encrypted wallet to `path`, replacing any existing file atomically. On unix
+    /// the file is only readable by its owner.
+    pub fn save(&self, path: impl AsRef<Path>, passphrase: &str, t: KDFType) -> Result<()> {
+        let path = path.as_ref();
+        let file = self.to_file(passphrase, t)?;
+
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| WalletError::Io(format!("{} is not a file", path.display())))?;
+        let mut tmp_name = file_name.to_os_string();
+        tmp_name.push(format!(".{}.tmp", uuid::Uuid::new_v4()));
+        let tmp = path.with_file_name(tmp_name);
+
+        let result = write_private(&tmp, file.as_bytes()).and_then(|_| fs::rename(&tmp, path));
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+
+        result.map_err(|err| WalletError::Io(err.to_string()))
+    }
+
+    pub fn load(path: impl AsRef<Path>, passphrase: &str) -> Result<Self> {
+        let file = fs::read_to_string(path).map_err(|err| WalletError::Io(err.to_string()))?;
+        Self::from_file(&file, passphrase)
+    }
+
+    /// Inserts `account`, making it the default account if there is none yet, and returns its
+    /// address.
+    fn insert(&self, account: Account) -> String {
+        let address = account.address().to_string();
+
+        let mut inner = self.inner.write().unwrap();
+        inner.accounts.insert(address.clone(), Arc::new(account));
+
+        if inner.default_account.is_none() {
+            inner.default_account = Some(address.clone());
+        }
+
+        address
+    }
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+fn sign_with_account(account: &Account, tx: &mut Transaction) -> Result<()> {
+    if account.is_watch_only() {
+        return Err(WalletError::WatchOnly(account.address().to_string()));
+    }
+
+    account
+        .sign_transaction(tx)
+        .map_err(|err| WalletError::Signing(err.to_string()))
+}
+
+fn normalize(address: &str) -> Result<String> {
+    normalize_address(address).map_err(|_| WalletError::InvalidAddress(address.to_string()))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{core::Transaction, provider::Provider, util::pack};
+    use crate::{
+        bech32::to_bech32_addr, keytools::generate_private_key, mock::MockServer,
+        provider::Provider, test_support,
+    };
 
     use super::*;
 
     #[test]
-    fn test_payload() {
-        let mut wallet = Wallet::new();
+    fn test_add_by_private_key() {
+        let wallet = Wallet::new();
+        assert!(wallet
+            .add_by_private_key(
+                "0x24180e6b0c3021aedb8f5a86f75276ee6fc7ff46e67e98e716728326102e91c9"
+            )
+            .is_ok());
+        assert_eq!(
+            wallet.add_by_private_key("not a key"),
+            Err(WalletError::Key(KeyError::InvalidHex))
+        );
+        assert_eq!(wallet.len(), 1);
+    }
+
+    #[test]
+    fn test_manage_accounts() {
+        let wallet = Wallet::new();
+        wallet
+            .add_by_private_key("24180e6b0c3021aedb8f5a86f75276ee6fc7ff46e67e98e716728326102e91c9")
+            .unwrap();
+        wallet
+            .add_by_private_key("e19d05c5452598e24caad4a0d85a49146f7be089515c905ae6a19e8a578a6930")
+            .unwrap();
+
+        let addresses = wallet
+            .accounts()
+            .iter()
+            .map(|account| account.address().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            addresses,
+            vec![
+                "9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a",
+                "b5c2cdd79c37209c3cb59e04b7c4062a8f5d5271"
+            ]
+        );
+        assert_eq!(
+            wallet.default_account().unwrap().address(),
+            "b5c2cdd79c37209c3cb59e04b7c4062a8f5d5271"
+        );
+
+        let bech32 = to_bech32_addr("9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a").unwrap();
+        assert!(wallet.contains(&bech32));
+        assert!(wallet.contains("0x9BFEC715A6BD658FCB62B0F8CC9BFA2ADE71434A"));
+        assert_eq!(
+            wallet.get_account("zil1invalid").err(),
+            Some(WalletError::InvalidAddress("zil1invalid".into()))
+        );
+
+        wallet.set_default_account(&bech32).unwrap();
+        assert_eq!(
+            wallet.default_account().unwrap().address(),
+            "9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a"
+        );
+
+        let mut tx = test_support::payment();
         wallet
-            .add_by_private_key("e19d05c5452598e24caad4a0d85a49146f7be089515c905ae6a19e8a578a6930");
-        let provider = Provider::new("https://dev-api.zilliqa.com/".into());
-
-        let gas_price = provider.get_minimum_gas_price().unwrap();
-
-        let tx = Transaction {
-            version: pack(333, 1).to_string(),
-            sender_pub_key: "0246E7178DC8253201101E18FD6F6EB9972451D121FC57AA2A06DD5C111E58DC6A"
-                .into(),
-            to_addr: "4BAF5faDA8e5Db92C3d3242618c5B47133AE003C".into(),
-            amount: "10000000".into(),
-            gas_price: gas_price,
-            gas_limit: "50".into(),
-            code: None,
-            data: None,
-            priority: false,
-            id: None,
-            nonce: None,
-            receipt: None,
-            signature: None,
+            .sign_with("0xb5c2cdd79c37209c3cb59e04b7c4062a8f5d5271", &mut tx)
+            .unwrap();
+        assert_eq!(
+            tx.sender_pub_key,
+            hex::encode(
+                wallet
+                    .get_account("b5c2cdd79c37209c3cb59e04b7c4062a8f5d5271")
+                    .unwrap()
+                    .public_key()
+                    .unwrap()
+            )
+        );
+
+        wallet.remove_account(&bech32).unwrap();
+        assert!(wallet.default_account().is_none());
+        assert_eq!(wallet.len(), 1);
+        assert_eq!(
+            wallet.remove_account(&bech32).err(),
+            Some(WalletError::AccountNotFound(
+                "9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a".into()
+            ))
+        );
+        assert_eq!(
+            wallet.sign(&mut test_support::payment()),
+            Err(WalletError::NoDefaultAccount)
+        );
+    }
+
+    #[test]
+    fn test_to_file_and_from_file() {
+        let wallet = Wallet::new();
+        wallet
+            .add_by_private_key("24180e6b0c3021aedb8f5a86f75276ee6fc7ff46e67e98e716728326102e91c9")
+            .unwrap();
+        wallet
+            .add_by_private_key("e19d05c5452598e24caad4a0d85a49146f7be089515c905ae6a19e8a578a6930")
+            .unwrap();
+        wallet
+            .set_label("9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a", "savings")
+            .unwrap();
+        wallet
+            .set_default_account("9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a")
+            .unwrap();
+
+        let file = wallet.to_file("xiaohuo", KDFType::Scrypt).unwrap();
+        assert!(!file.contains("24180e6b0c3021aedb8f5a86f75276ee6fc7ff46e67e98e716728326102e91c9"));
+
+        let loaded = Wallet::from_file(&file, "xiaohuo").unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(
+            loaded.default_account().unwrap().address(),
+            "9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a"
+        );
+        assert_eq!(
+            loaded.label("0x9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a"),
+            Some("savings".into())
+        );
+        assert_eq!(
+            loaded.label("b5c2cdd79c37209c3cb59e04b7c4062a8f5d5271"),
+            None
+        );
+
+        assert!(matches!(
+            Wallet::from_file(&file, "wrong"),
+            Err(WalletError::Crypto(_))
+        ));
+        assert_eq!(
+            Wallet::from_file(&file.replace("\"version\":2", "\"version\":3"), "xiaohuo").err(),
+            Some(WalletError::UnsupportedVersion(3))
+        );
+    }
+
+    #[test]
+    fn test_watch_only() {
+        let wallet = Wallet::new();
+        wallet
+            .add_watch_only(
+                "0x9BFEC715A6BD658FCB62B0F8CC9BFA2ADE71434A",
+                Some("0246E7178DC8253201101E18FD6F6EB9972451D121FC57AA2A06DD5C111E58DC6A"),
+            )
+            .unwrap();
+        wallet
+            .add_watch_only(test_support::RECIPIENT, None)
+            .unwrap();
+        wallet
+            .add_by_private_key("24180e6b0c3021aedb8f5a86f75276ee6fc7ff46e67e98e716728326102e91c9")
+            .unwrap();
+        assert_eq!(wallet.len(), 3);
+
+        assert_eq!(
+            wallet.default_account().unwrap().address(),
+            "b5c2cdd79c37209c3cb59e04b7c4062a8f5d5271"
+        );
+        assert_eq!(
+            wallet.set_default_account("9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a"),
+            Err(WalletError::WatchOnly(
+                "9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a".into()
+            ))
+        );
+
+        let mut tx = test_support::payment();
+        assert_eq!(
+            wallet.sign_with("9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a", &mut tx),
+            Err(WalletError::WatchOnly(
+                "9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a".into()
+            ))
+        );
+
+        wallet
+            .prepare_transaction("9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a", &mut tx)
+            .unwrap();
+        assert_eq!(
+            tx.sender_pub_key,
+            "0246e7178dc8253201101e18fd6f6eb9972451d121fc57aa2a06dd5c111e58dc6a"
+        );
+        assert!(tx.signature.is_none());
+        assert_eq!(
+            wallet.prepare_transaction(test_support::RECIPIENT, &mut tx),
+            Err(WalletError::MissingPublicKey(
+                "4baf5fada8e5db92c3d3242618c5b47133ae003c".into()
+            ))
+        );
+
+        assert_eq!(
+            wallet.add_watch_only("b5c2cdd79c37209c3cb59e04b7c4062a8f5d5271", None),
+            Err(WalletError::AccountExists(
+                "b5c2cdd79c37209c3cb59e04b7c4062a8f5d5271".into()
+            ))
+        );
+        assert_eq!(
+            wallet.add_watch_only(
+                test_support::RECIPIENT,
+                Some("0246E7178DC8253201101E18FD6F6EB9972451D121FC57AA2A06DD5C111E58DC6A")
+            ),
+            Err(WalletError::InvalidPublicKey(
+                "4baf5fada8e5db92c3d3242618c5b47133ae003c".into()
+            ))
+        );
+
+        let loaded = Wallet::from_file(
+            &wallet.to_file("xiaohuo", KDFType::Scrypt).unwrap(),
+            "xiaohuo",
+        )
+        .unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert!(loaded
+            .get_account("4baf5fada8e5db92c3d3242618c5b47133ae003c")
+            .unwrap()
+            .is_watch_only());
+        assert_eq!(
+            loaded.default_account().unwrap().address(),
+            "b5c2cdd79c37209c3cb59e04b7c4062a8f5d5271"
+        );
+
+        let watch_only = Wallet::new();
+        watch_only
+            .add_watch_only(test_support::RECIPIENT, None)
+            .unwrap();
+        assert!(watch_only.default_account().is_none());
+        assert_eq!(
+            watch_only.sign(&mut test_support::payment()),
+            Err(WalletError::NoDefaultAccount)
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("wallet-{}.json", uuid::Uuid::new_v4()));
+
+        let wallet = Wallet::new();
+        wallet.save(&path, "xiaohuo", KDFType::Scrypt).unwrap();
+        wallet.save(&path, "xiaohuo", KDFType::Scrypt).unwrap();
+
+        let loaded = Wallet::load(&path, "xiaohuo").unwrap();
+        assert!(loaded.is_empty());
+        assert!(loaded.default_account().is_none());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let tmp = path.with_extension("tmp");
+        wallet.save(&tmp, "xiaohuo", KDFType::Scrypt).unwrap();
+        assert!(Wallet::load(&tmp, "xiaohuo").unwrap().is_empty());
+        fs::remove_file(&tmp).unwrap();
+
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            Wallet::load(&path, "xiaohuo"),
+            Err(WalletError::Io(_))
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_share_between_tasks() {
+        let wallet = Wallet::new();
+        assert_eq!(
+            wallet.sign(&mut test_support::payment()),
+            Err(WalletError::NoDefaultAccount)
+        );
+
+        wallet
+            .add_by_private_key("e19d05c5452598e24caad4a0d85a49146f7be089515c905ae6a19e8a578a6930")
+            .unwrap();
+
+        let mut handles = Vec::new();
+        for i in 0..4 {
+            let wallet = wallet.clone();
+            handles.push(tokio::spawn(async move {
+                if i % 2 == 0 {
+                    let mut tx = test_support::payment();
+                    wallet.sign(&mut tx).unwrap();
+                    assert!(tx.signature.is_some());
+                } else {
+                    wallet
+                        .add_by_private_key(&hex::encode(generate_private_key().unwrap()))
+                        .unwrap();
+                }
+            }));
+        }
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(wallet.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_payload() {
+        let wallet = Wallet::new();
+        wallet
+            .add_by_private_key("e19d05c5452598e24caad4a0d85a49146f7be089515c905ae6a19e8a578a6930")
+            .unwrap();
+        let server = MockServer::start().with_fixtures();
+        let provider = Provider::new(server.url());
+
+        let gas_price = provider.get_minimum_gas_price().await.unwrap();
+
+        let mut tx = Transaction {
+            gas_price,
+            ..test_support::payment()
         };
 
-        todo!("{:?}", tx);
+        wallet.sign(&mut tx).unwrap();
+        let response = provider.create_transaction(&tx).await.unwrap();
+        assert!(!response.tran_id.is_empty());
+
+        let params = &server.calls_to("CreateTransaction")[0].params[0];
+        assert_eq!(params["gasPrice"], "2000000000");
+        assert_eq!(params["pubKey"], tx.sender_pub_key);
+        assert_eq!(params["signature"], tx.signature.unwrap());
     }
 }
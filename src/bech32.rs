@@ -1,4 +1,4 @@
-use bech32::FromBase32;
+use bech32::{FromBase32, ToBase32, Variant};
 use std::error::Error;
 
 const HRP: &str = "zil";
@@ -15,6 +15,16 @@ pub fn from_bech32_addr(address: &str) -> Result<String, Box<dyn Error>> {
     Ok(hex::encode(&conv))
 }
 
+pub fn to_bech32_addr(address: &str) -> Result<String, Box<dyn Error>> {
+    let address = hex::decode(address.trim_start_matches("0x"))?;
+
+    if address.len() != 20 {
+        return Err(String::from("expected address to be 20 bytes").into());
+    }
+
+    Ok(bech32::encode(HRP, address.to_base32(), Variant::Bech32)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,4 +103,17 @@ mod tests {
             "5f5db1c18ccde67e513b7f7ae820e569154976ba"
         );
     }
+
+    #[test]
+    fn test_to_bech32_addr() {
+        assert_eq!(
+            to_bech32_addr("0x4BAF5faDA8e5Db92C3d3242618c5B47133AE003C").unwrap(),
+            "zil1fwh4ltdguhde9s7nysnp33d5wye6uqpugufkz7"
+        );
+        assert_eq!(
+            to_bech32_addr("1d19918a737306218b5cbb3241fcdcbd998c3a72").unwrap(),
+            "zil1r5verznnwvrzrz6uhveyrlxuhkvccwnju4aehf"
+        );
+        assert!(to_bech32_addr("1d19918a").is_err());
+    }
 }
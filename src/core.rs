@@ -117,9 +117,153 @@ pub struct TxBlock {
     pub header: TxBlockHeader,
 }
 
+/// One page of the transactions in a TxBlock, with pages counted from 0. `T` is a list of
+/// hashes per micro block for `GetTransactionsForTxBlockEx` and a full [`Transaction`] for
+/// `GetTxBodiesForTxBlockEx`.
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct TransactionsPage<T> {
+    pub curr_page: u32,
+    pub num_pages: u32,
+    pub transactions: Vec<T>,
+}
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SmartContractCode {
+    pub code: String,
+}
+
 #[repr(C)]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BalanceAndNonce {
     pub balance: String,
     pub nonce: u64,
 }
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateTransactionResponse {
+    #[serde(rename = "Info")]
+    pub info: String,
+    #[serde(rename = "TranID")]
+    pub tran_id: String,
+    #[serde(rename = "ContractAddress")]
+    pub contract_address: Option<String>,
+}
+
+/// The number of nodes in each shard.
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ShardingStructure {
+    pub num_peers: Vec<u32>,
+}
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct BlockchainInfo {
+    #[serde(rename = "CurrentDSEpoch")]
+    pub current_ds_epoch: String,
+    pub current_mini_epoch: String,
+    #[serde(rename = "DSBlockRate")]
+    pub ds_block_rate: f64,
+    #[serde(rename = "NumDSBlocks")]
+    pub num_ds_blocks: String,
+    pub num_peers: u32,
+    pub num_transactions: String,
+    pub num_tx_blocks: String,
+    #[serde(rename = "NumTxnsDSEpoch")]
+    pub num_txns_ds_epoch: String,
+    pub num_txns_tx_epoch: String,
+    pub sharding_structure: ShardingStructure,
+    pub transaction_rate: f64,
+    pub tx_block_rate: f64,
+}
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct DsBlockHeader {
+    pub block_num: String,
+    /// The minimum PoW difficulty to join a shard in this epoch.
+    pub difficulty: u32,
+    /// The minimum PoW difficulty to join the DS committee in this epoch.
+    #[serde(rename = "DifficultyDS")]
+    pub difficulty_ds: u32,
+    pub gas_price: String,
+    pub leader_pub_key: String,
+    /// The public keys of the nodes that joined the DS committee in this epoch.
+    #[serde(rename = "PoWWinners")]
+    pub pow_winners: Vec<String>,
+    pub prev_hash: String,
+    pub timestamp: String,
+}
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DsBlock {
+    pub header: DsBlockHeader,
+    pub signature: String,
+}
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct BlockListingEntry {
+    pub block_num: u64,
+    pub hash: String,
+}
+
+/// A page of the most recent blocks, newest first.
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlockListing {
+    pub data: Vec<BlockListingEntry>,
+    #[serde(rename = "maxPages")]
+    pub max_pages: u32,
+}
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShardInfo {
+    pub nodes: Vec<String>,
+    pub size: u32,
+}
+
+/// The DS committee and shard members of a DS epoch.
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MinerInfo {
+    #[serde(rename = "dscommittee")]
+    pub ds_committee: Vec<String>,
+    pub shards: Vec<ShardInfo>,
+}
+
+/// A `NewBlock` notification of the WebSocket API.
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct NewBlock {
+    #[serde(rename = "TxBlock")]
+    pub tx_block: TxBlock,
+    #[serde(rename = "TxHashes")]
+    pub tx_hashes: Vec<Vec<String>>,
+}
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EventLog {
+    #[serde(rename = "_eventname")]
+    pub event_name: String,
+    pub params: Vec<ContractValue>,
+}
+
+/// The events a contract emitted in a block, as sent by the `EventLog` subscription.
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContractEventLogs {
+    pub address: String,
+    pub event_logs: Vec<EventLog>,
+}
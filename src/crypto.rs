@@ -1,3 +1,6 @@
+pub mod ecies;
 pub mod keystore;
+pub mod musig;
 pub mod pbkdf2;
+pub mod schnorr;
 pub mod scrypt;
@@ -0,0 +1,140 @@
+use std::error::Error;
+
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
+use hkdf::Hkdf;
+use rand::RngCore;
+use secp256k1::ecdh::SharedSecret;
+use secp256k1::rand::rngs::OsRng;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use sha2::Sha256;
+
+use crate::core::Transaction;
+use crate::keytools::get_address_from_public_key;
+use crate::util::normalize_address;
+
+const HKDF_INFO: &[u8] = b"zilliqa-sdk/ecies/aes-256-gcm";
+const PUBLIC_KEY_SIZE: usize = 33;
+const NONCE_SIZE: usize = 12;
+
+/// Encrypts `plain_text` to the owner of `public_key`.
+///
+/// The output is `ephemeral public key (33 bytes) || nonce (12 bytes) || AES-256-GCM cipher text`,
+/// where the AES key is derived with HKDF-SHA256 from the ECDH shared secret, salted with the
+/// ephemeral public key.
+pub fn encrypt(public_key: &[u8], plain_text: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let public_key = PublicKey::from_slice(public_key)?;
+
+    let mut rng = OsRng::new()?;
+    let ephemeral_key = SecretKey::new(&mut rng);
+    let ephemeral_public_key =
+        PublicKey::from_secret_key(&Secp256k1::new(), &ephemeral_key).serialize();
+
+    let mut nonce = [0u8; NONCE_SIZE];
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let cipher = cipher(&public_key, &ephemeral_key, &ephemeral_public_key);
+    let cipher_text = cipher
+        .encrypt(Nonce::from_slice(&nonce), plain_text)
+        .map_err(|_| String::from("Failed to encrypt."))?;
+
+    let mut ret = Vec::with_capacity(PUBLIC_KEY_SIZE + NONCE_SIZE + cipher_text.len());
+    ret.extend_from_slice(&ephemeral_public_key);
+    ret.extend_from_slice(&nonce);
+    ret.extend_from_slice(&cipher_text);
+
+    Ok(ret)
+}
+
+pub fn decrypt(private_key: &[u8], cipher_text: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let private_key = SecretKey::from_slice(private_key)?;
+
+    if cipher_text.len() < PUBLIC_KEY_SIZE + NONCE_SIZE {
+        return Err(String::from("cipher text is too short").into());
+    }
+
+    let (ephemeral_public_key, rest) = cipher_text.split_at(PUBLIC_KEY_SIZE);
+    let (nonce, cipher_text) = rest.split_at(NONCE_SIZE);
+
+    let cipher = cipher(
+        &PublicKey::from_slice(ephemeral_public_key)?,
+        &private_key,
+        ephemeral_public_key,
+    );
+
+    let plain_text = cipher
+        .decrypt(Nonce::from_slice(nonce), cipher_text)
+        .map_err(|_| String::from("Failed to decrypt."))?;
+
+    Ok(plain_text)
+}
+
+/// Returns the compressed public key that signed `tx`, checking that it belongs to `address`.
+/// Useful to encrypt to a counterparty known only by their address once they have sent a
+/// transaction.
+pub fn sender_public_key(tx: &Transaction, address: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let public_key = hex::decode(tx.sender_pub_key.trim_start_matches("0x"))?;
+    let public_key = PublicKey::from_slice(&public_key)?.serialize().to_vec();
+
+    if get_address_from_public_key(&public_key)? != normalize_address(address)? {
+        return Err(format!("transaction was not sent by {}", address).into());
+    }
+
+    Ok(public_key)
+}
+
+fn cipher(public_key: &PublicKey, secret_key: &SecretKey, salt: &[u8]) -> Aes256Gcm {
+    let shared_secret = SharedSecret::new(public_key, secret_key);
+
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(salt), &shared_secret.secret_bytes())
+        .expand(HKDF_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keytools::get_public_key_from_private_key;
+    use crate::test_support;
+
+    const PRIVATE_KEY: &str = "24180e6b0c3021aedb8f5a86f75276ee6fc7ff46e67e98e716728326102e91c9";
+
+    #[test]
+    fn test_encrypt_and_decrypt() {
+        let private_key = hex::decode(PRIVATE_KEY).unwrap();
+        let public_key = get_public_key_from_private_key(&private_key, true).unwrap();
+
+        let cipher_text = encrypt(&public_key, b"invoice #42").unwrap();
+        assert_eq!(cipher_text.len(), 33 + 12 + 11 + 16);
+        assert_eq!(decrypt(&private_key, &cipher_text).unwrap(), b"invoice #42");
+
+        let other = hex::decode("af71626e38926401a6d2fd8fdf91c97f785b8fb2b867e7f8a884351e59ee9aa6")
+            .unwrap();
+        assert!(decrypt(&other, &cipher_text).is_err());
+
+        let mut tampered = cipher_text.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&private_key, &tampered).is_err());
+        assert!(decrypt(&private_key, &cipher_text[..40]).is_err());
+    }
+
+    #[test]
+    fn test_sender_public_key() {
+        let tx = Transaction {
+            sender_pub_key: format!("0x{}", test_support::SENDER_PUBLIC_KEY),
+            ..test_support::payment()
+        };
+
+        let public_key =
+            sender_public_key(&tx, "0x9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a").unwrap();
+        assert_eq!(
+            hex::encode(public_key),
+            "0246e7178dc8253201101e18fd6f6eb9972451d121fc57aa2a06dd5c111e58dc6a"
+        );
+
+        assert!(sender_public_key(&tx, test_support::RECIPIENT).is_err());
+    }
+}
@@ -127,6 +127,37 @@ impl KeyStore {
         t: KDFType,
     ) -> Result<String, Box<dyn Error>> {
         let address = get_address_from_private_key(private_key)?;
+        let crypto = self.encrypt(private_key, passphrase, t)?;
+
+        let uid = Uuid::new_v4();
+        let kv = KeyStoreV3 {
+            address,
+            crypto,
+            id: uid.to_string(),
+            version: 3,
+        };
+
+        Ok(serde_json::to_string(&kv).unwrap())
+    }
+
+    pub fn decrypt_private_key(
+        &self,
+        json: &str,
+        passphrase: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let kv = serde_json::from_str::<KeyStoreV3>(json)?;
+        let private_key = self.decrypt(&kv.crypto, passphrase.as_bytes())?;
+
+        Ok(hex::encode(&private_key))
+    }
+
+    /// Encrypts arbitrary data the same way private keys are encrypted in a V3 key store.
+    pub fn encrypt(
+        &self,
+        data: &[u8],
+        passphrase: &[u8],
+        t: KDFType,
+    ) -> Result<Crypto, Box<dyn Error>> {
         let iv = generate_random_bytes(16);
         let salt = generate_random_bytes(32);
 
@@ -139,7 +170,7 @@ impl KeyStore {
 
         let encrypt_key = &derived_key[0..16];
 
-        let mut cipher_text = private_key.to_vec();
+        let mut cipher_text = data.to_vec();
         let mut cipher = Aes128Ctr64LE::new(encrypt_key.into(), iv[0..16].into());
         cipher.apply_keystream(&mut cipher_text);
 
@@ -151,48 +182,31 @@ impl KeyStore {
 
         let kp = KDFParams::new(hex::encode(salt));
 
-        let crypto = Crypto {
+        Ok(Crypto {
             cipher: "aes-128-ctr".to_string(),
             cipher_params: cp,
             cipher_text: hex::encode(cipher_text),
             kdf: t,
             kdf_params: kp,
             mac: hex::encode(mac),
-        };
-
-        let uid = Uuid::new_v4();
-        let kv = KeyStoreV3 {
-            address,
-            crypto,
-            id: uid.to_string(),
-            version: 3,
-        };
-
-        Ok(serde_json::to_string(&kv).unwrap())
+        })
     }
 
-    pub fn decrypt_private_key(
-        &self,
-        json: &str,
-        passphrase: &str,
-    ) -> Result<String, Box<dyn Error>> {
-        let kv = serde_json::from_str::<KeyStoreV3>(json)?;
+    pub fn decrypt(&self, crypto: &Crypto, passphrase: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        let cipher_text = hex::decode(&crypto.cipher_text)?;
+        let iv = hex::decode(&crypto.cipher_params.iv)?;
+        let kdf_params = &crypto.kdf_params;
 
-        let cipher_text = hex::decode(&kv.crypto.cipher_text)?;
-        let iv = hex::decode(kv.crypto.cipher_params.iv)?;
-        let kdf_params = kv.crypto.kdf_params;
-        let kdf = kv.crypto.kdf;
-
-        let derived_key = match kdf {
+        let derived_key = match crypto.kdf {
             KDFType::PBKDF2 => self.pbkdf2.get_derived_key(
-                passphrase.as_bytes(),
-                &hex::decode(kdf_params.salt)?,
+                passphrase,
+                &hex::decode(&kdf_params.salt)?,
                 262144,
                 32,
             )?,
             KDFType::Scrypt => self.scrypt.get_derived_key(
-                passphrase.as_bytes(),
-                &hex::decode(kdf_params.salt)?,
+                passphrase,
+                &hex::decode(&kdf_params.salt)?,
                 8192,
                 8,
                 1,
@@ -202,17 +216,17 @@ impl KeyStore {
 
         let mac = hex::encode(generate_mac(&derived_key, &cipher_text, &iv));
 
-        if mac.to_lowercase() != kv.crypto.mac.to_lowercase() {
+        if mac.to_lowercase() != crypto.mac.to_lowercase() {
             return Err(String::from("Failed to decrypt.").into());
         }
 
         let encrypt_key = &derived_key[0..16];
 
-        let mut private_key = cipher_text.to_vec();
+        let mut data = cipher_text.to_vec();
         let mut cipher = Aes128Ctr64LE::new(encrypt_key.into(), iv[0..16].into());
-        cipher.apply_keystream(&mut private_key);
+        cipher.apply_keystream(&mut data);
 
-        Ok(hex::encode(&private_key))
+        Ok(data)
     }
 }
 
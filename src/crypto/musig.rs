@@ -0,0 +1,573 @@
+//! MuSig2 multi-party Schnorr signatures (https://eprint.iacr.org/2020/1261).
+//!
+//! The public keys of all parties aggregate into a single ordinary public key, so the jointly
+//! controlled account is indistinguishable from any other Zilliqa account and the final
+//! signature verifies with [`super::schnorr::verify`].
+//!
+//! Signing takes two rounds: parties exchange [`PublicNonce`]s, then [`PartialSignature`]s.
+//! Both are serializable so they can be sent over any transport.
+
+use std::collections::BTreeMap;
+
+use num_bigint::BigUint;
+use secp256k1::rand::rngs::OsRng;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::schnorr::{challenge, curve_order, to_scalar_bytes, Signature};
+use crate::core::Transaction;
+use crate::keytools::get_address_from_public_key;
+use crate::transaction::encode_transaction_proto;
+use crate::util::sha_256;
+
+const TAG_KEY_AGG_LIST: &[u8] = b"Zilliqa/MuSig2/KeyAggList";
+const TAG_KEY_AGG_COEFFICIENT: &[u8] = b"Zilliqa/MuSig2/KeyAggCoefficient";
+const TAG_NONCE_COEFFICIENT: &[u8] = b"Zilliqa/MuSig2/NonceCoefficient";
+
+#[derive(Error, Debug)]
+pub enum MusigError {
+    #[error("invalid public key {0}")]
+    InvalidPublicKey(String),
+    #[error("invalid private key")]
+    InvalidPrivateKey,
+    #[error("public key {0} is not part of the aggregated key")]
+    UnknownSigner(String),
+    #[error("invalid nonce from {0}")]
+    InvalidNonce(String),
+    #[error("a nonce from {0} has already been added")]
+    DuplicateNonce(String),
+    #[error("received a nonce for this session's own public key")]
+    OwnNonce,
+    #[error("invalid partial signature from {0}")]
+    InvalidPartialSignature(String),
+    #[error("waiting for {0} more nonces")]
+    MissingNonces(usize),
+    #[error("waiting for {0} more partial signatures")]
+    MissingPartialSignatures(usize),
+    #[error("the secret nonce of this session has already been used")]
+    NonceUsed,
+    #[error("failed to encode transaction: {0}")]
+    Transaction(String),
+    #[error("degenerate signing session, retry with fresh nonces")]
+    Degenerate,
+    #[error("failed to access the system random number generator: {0}")]
+    Rng(#[from] secp256k1::rand::Error),
+}
+
+pub type Result<T> = std::result::Result<T, MusigError>;
+
+/// Round one message: the two public nonces of a signer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PublicNonce {
+    pub public_key: String,
+    pub r1: String,
+    pub r2: String,
+}
+
+/// Round two message: the partial signature of a signer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PartialSignature {
+    pub public_key: String,
+    pub s: String,
+}
+
+/// The aggregated public key of a set of signers. Keys are sorted first, so every party derives
+/// the same aggregate regardless of the order in which they learned about each other.
+#[derive(Debug, Clone)]
+pub struct KeyAggContext {
+    coefficients: BTreeMap<[u8; 33], BigUint>,
+    aggregate: PublicKey,
+}
+
+impl KeyAggContext {
+    pub fn new(public_keys: &[Vec<u8>]) -> Result<Self> {
+        let mut keys = public_keys
+            .iter()
+            .map(|key| {
+                PublicKey::from_slice(key)
+                    .map(|key| key.serialize())
+                    .map_err(|_| MusigError::InvalidPublicKey(hex::encode(key)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        keys.sort_unstable();
+        keys.dedup();
+
+        if keys.is_empty() {
+            return Err(MusigError::InvalidPublicKey(String::new()));
+        }
+
+        let list_hash = tagged_hash(TAG_KEY_AGG_LIST, &keys.concat());
+        let context = Secp256k1::new();
+
+        let mut coefficients = BTreeMap::new();
+        let mut points = Vec::new();
+        for key in keys {
+            let coefficient = scalar(&tagged_hash(
+                TAG_KEY_AGG_COEFFICIENT,
+                &[&list_hash[..], &key[..]].concat(),
+            ));
+
+            let mut point = PublicKey::from_slice(&key).unwrap();
+            point
+                .mul_assign(&context, &to_scalar_bytes(&coefficient))
+                .map_err(|_| MusigError::InvalidPublicKey(hex::encode(key)))?;
+
+            points.push(point);
+            coefficients.insert(key, coefficient);
+        }
+
+        let aggregate = PublicKey::combine_keys(&points.iter().collect::<Vec<_>>())
+            .map_err(|_| MusigError::Degenerate)?;
+
+        Ok(Self {
+            coefficients,
+            aggregate,
+        })
+    }
+
+    /// The compressed aggregated public key, used as `sender_pub_key` of joint transactions.
+    pub fn aggregate_public_key(&self) -> Vec<u8> {
+        self.aggregate.serialize().to_vec()
+    }
+
+    /// The Zilliqa address controlled jointly by all signers.
+    pub fn address(&self) -> String {
+        get_address_from_public_key(&self.aggregate.serialize()).unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    fn coefficient(&self, public_key: &[u8; 33]) -> Result<&BigUint> {
+        self.coefficients
+            .get(public_key)
+            .ok_or_else(|| MusigError::UnknownSigner(hex::encode(public_key)))
+    }
+}
+
+struct SecretNonce {
+    k1: BigUint,
+    k2: BigUint,
+}
+
+/// The state of one signer during a signing session. A session must only ever be used to sign
+/// a single message; its secret nonce is erased once the partial signature is produced.
+pub struct Session {
+    key_agg: KeyAggContext,
+    secret_key: SecretKey,
+    public_key: [u8; 33],
+    message: Vec<u8>,
+    secret_nonce: Option<SecretNonce>,
+    public_nonce: PublicNonce,
+    nonces: BTreeMap<[u8; 33], (PublicKey, PublicKey)>,
+    partial_signatures: BTreeMap<[u8; 33], BigUint>,
+}
+
+impl Session {
+    pub fn new(key_agg: KeyAggContext, private_key: &[u8], message: &[u8]) -> Result<Self> {
+        let context = Secp256k1::new();
+        let secret_key =
+            SecretKey::from_slice(private_key).map_err(|_| MusigError::InvalidPrivateKey)?;
+        let public_key = PublicKey::from_secret_key(&context, &secret_key).serialize();
+        key_agg.coefficient(&public_key)?;
+
+        let mut rng = OsRng::new()?;
+        let k1 = SecretKey::new(&mut rng);
+        let k2 = SecretKey::new(&mut rng);
+        let r1 = PublicKey::from_secret_key(&context, &k1);
+        let r2 = PublicKey::from_secret_key(&context, &k2);
+
+        let public_nonce = PublicNonce {
+            public_key: hex::encode(public_key),
+            r1: hex::encode(r1.serialize()),
+            r2: hex::encode(r2.serialize()),
+        };
+
+        let mut nonces = BTreeMap::new();
+        nonces.insert(public_key, (r1, r2));
+
+        Ok(Self {
+            key_agg,
+            secret_key,
+            public_key,
+            message: message.to_vec(),
+            secret_nonce: Some(SecretNonce {
+                k1: BigUint::from_bytes_be(&k1.secret_bytes()),
+                k2: BigUint::from_bytes_be(&k2.secret_bytes()),
+            }),
+            public_nonce,
+            nonces,
+            partial_signatures: BTreeMap::new(),
+        })
+    }
+
+    /// Starts a session signing `tx` on behalf of the aggregated key. `sender_pub_key` is set to
+    /// the aggregated public key, so every party must pass an identical transaction.
+    pub fn for_transaction(
+        key_agg: KeyAggContext,
+        private_key: &[u8],
+        tx: &mut Transaction,
+    ) -> Result<Self> {
+        tx.sender_pub_key = hex::encode(key_agg.aggregate_public_key());
+        let message =
+            encode_transaction_proto(tx).map_err(|err| MusigError::Transaction(err.to_string()))?;
+
+        Self::new(key_agg, private_key, &message)
+    }
+
+    /// The round one message to send to every other signer.
+    pub fn public_nonce(&self) -> PublicNonce {
+        self.public_nonce.clone()
+    }
+
+    /// Adds the round one message of another signer. Each signer's nonce can only be added
+    /// once.
+    pub fn add_nonce(&mut self, nonce: PublicNonce) -> Result<()> {
+        let public_key = parse_public_key(&nonce.public_key)?;
+        self.key_agg.coefficient(&public_key)?;
+        if public_key == self.public_key {
+            return Err(MusigError::OwnNonce);
+        }
+        if self.nonces.contains_key(&public_key) {
+            return Err(MusigError::DuplicateNonce(nonce.public_key));
+        }
+
+        let parse_point = |point: &str| {
+            hex::decode(point)
+                .ok()
+                .and_then(|point| PublicKey::from_slice(&point).ok())
+                .ok_or_else(|| MusigError::InvalidNonce(nonce.public_key.clone()))
+        };
+
+        let points = (parse_point(&nonce.r1)?, parse_point(&nonce.r2)?);
+        self.nonces.insert(public_key, points);
+
+        Ok(())
+    }
+
+    /// Produces the round two message. Requires the nonces of all signers.
+    pub fn partial_sign(&mut self) -> Result<PartialSignature> {
+        let (b, r) = self.nonce_coefficient()?;
+        let secret_nonce = self.secret_nonce.take().ok_or(MusigError::NonceUsed)?;
+
+        let n = curve_order();
+        let c = self.challenge(&r)?;
+        let a = self.key_agg.coefficient(&self.public_key)?;
+        let x = BigUint::from_bytes_be(&self.secret_key.secret_bytes());
+
+        // s_i = k1 + b * k2 - c * a_i * x_i
+        let s = (secret_nonce.k1 + &b * secret_nonce.k2 + &n - (c * a % &n) * x % &n) % &n;
+        self.partial_signatures.insert(self.public_key, s.clone());
+
+        Ok(PartialSignature {
+            public_key: hex::encode(self.public_key),
+            s: hex::encode(to_scalar_bytes(&s)),
+        })
+    }
+
+    /// Adds and verifies the partial signature of another signer.
+    pub fn add_partial_signature(&mut self, partial_signature: PartialSignature) -> Result<()> {
+        let public_key = parse_public_key(&partial_signature.public_key)?;
+        let invalid = || MusigError::InvalidPartialSignature(partial_signature.public_key.clone());
+
+        let s = hex::decode(&partial_signature.s)
+            .ok()
+            .filter(|s| s.len() == 32)
+            .map(|s| BigUint::from_bytes_be(&s))
+            .filter(|s| *s < curve_order())
+            .ok_or_else(invalid)?;
+
+        let (b, r) = self.nonce_coefficient()?;
+        let c = self.challenge(&r)?;
+        let a = self.key_agg.coefficient(&public_key)?;
+        let (r1, r2) = self.nonces.get(&public_key).ok_or_else(invalid)?;
+
+        // s_i * G + c * a_i * X_i == R_i1 + b * R_i2
+        let context = Secp256k1::new();
+        let lhs = point_mul(
+            &PublicKey::from_slice(&public_key).unwrap(),
+            &(c * a % curve_order()),
+        )
+        .and_then(|point| match SecretKey::from_slice(&to_scalar_bytes(&s)) {
+            Ok(s) => PublicKey::from_secret_key(&context, &s)
+                .combine(&point)
+                .ok(),
+            Err(_) => Some(point),
+        });
+        let rhs = point_mul(r2, &b).and_then(|point| r1.combine(&point).ok());
+
+        if lhs.is_none() || lhs != rhs {
+            return Err(invalid());
+        }
+
+        self.partial_signatures.insert(public_key, s);
+
+        Ok(())
+    }
+
+    /// Aggregates all partial signatures into an ordinary Zilliqa Schnorr signature.
+    pub fn finalize(&self) -> Result<Signature> {
+        let missing = self.key_agg.len() - self.partial_signatures.len();
+        if missing > 0 {
+            return Err(MusigError::MissingPartialSignatures(missing));
+        }
+
+        let n = curve_order();
+        let (_, r) = self.nonce_coefficient()?;
+        let c = self.challenge(&r)?;
+        let s = self
+            .partial_signatures
+            .values()
+            .fold(BigUint::default(), |acc, s| (acc + s) % &n);
+
+        if s == BigUint::default() {
+            return Err(MusigError::Degenerate);
+        }
+
+        Ok(Signature {
+            r: to_scalar_bytes(&c),
+            s: to_scalar_bytes(&s),
+        })
+    }
+
+    /// Returns the nonce coefficient b and the aggregated nonce R = R1 + b * R2.
+    fn nonce_coefficient(&self) -> Result<(BigUint, PublicKey)> {
+        let missing = self.key_agg.len() - self.nonces.len();
+        if missing > 0 {
+            return Err(MusigError::MissingNonces(missing));
+        }
+
+        let r1 =
+            PublicKey::combine_keys(&self.nonces.values().map(|(r1, _)| r1).collect::<Vec<_>>())
+                .map_err(|_| MusigError::Degenerate)?;
+        let r2 =
+            PublicKey::combine_keys(&self.nonces.values().map(|(_, r2)| r2).collect::<Vec<_>>())
+                .map_err(|_| MusigError::Degenerate)?;
+
+        let b = scalar(&tagged_hash(
+            TAG_NONCE_COEFFICIENT,
+            &[
+                &self.key_agg.aggregate.serialize()[..],
+                &r1.serialize()[..],
+                &r2.serialize()[..],
+                &self.message[..],
+            ]
+            .concat(),
+        ));
+
+        let r = point_mul(&r2, &b)
+            .and_then(|point| r1.combine(&point).ok())
+            .ok_or(MusigError::Degenerate)?;
+
+        Ok((b, r))
+    }
+
+    fn challenge(&self, r: &PublicKey) -> Result<BigUint> {
+        let c = challenge(r, &self.key_agg.aggregate.serialize(), &self.message);
+        if c == BigUint::default() {
+            return Err(MusigError::Degenerate);
+        }
+
+        Ok(c)
+    }
+}
+
+fn parse_public_key(public_key: &str) -> Result<[u8; 33]> {
+    hex::decode(public_key)
+        .ok()
+        .and_then(|key| PublicKey::from_slice(&key).ok())
+        .map(|key| key.serialize())
+        .ok_or_else(|| MusigError::InvalidPublicKey(public_key.to_string()))
+}
+
+fn point_mul(point: &PublicKey, scalar: &BigUint) -> Option<PublicKey> {
+    let mut ret = *point;
+    ret.mul_assign(&Secp256k1::new(), &to_scalar_bytes(scalar))
+        .ok()
+        .map(|_| ret)
+}
+
+fn scalar(hash: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(hash) % curve_order()
+}
+
+fn tagged_hash(tag: &[u8], data: &[u8]) -> Vec<u8> {
+    let tag = sha_256(tag);
+    sha_256(&[&tag[..], &tag[..], data].concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::schnorr::verify;
+    use crate::keytools::{generate_private_key, get_public_key_from_private_key};
+    use crate::test_support;
+
+    fn signers(n: usize) -> (Vec<Vec<u8>>, KeyAggContext) {
+        let private_keys = (0..n)
+            .map(|_| generate_private_key().unwrap())
+            .collect::<Vec<_>>();
+        let public_keys = private_keys
+            .iter()
+            .map(|key| get_public_key_from_private_key(key, true).unwrap())
+            .collect::<Vec<_>>();
+
+        (private_keys, KeyAggContext::new(&public_keys).unwrap())
+    }
+
+    fn run(sessions: &mut [Session]) -> Signature {
+        let nonces = sessions
+            .iter()
+            .map(|s| s.public_nonce())
+            .collect::<Vec<_>>();
+        for session in sessions.iter_mut() {
+            let own = session.public_nonce();
+            for nonce in nonces.iter().filter(|nonce| **nonce != own) {
+                session.add_nonce(nonce.clone()).unwrap();
+            }
+        }
+
+        let partials = sessions
+            .iter_mut()
+            .map(|s| s.partial_sign().unwrap())
+            .collect::<Vec<_>>();
+        for session in sessions.iter_mut() {
+            for partial in &partials {
+                session.add_partial_signature(partial.clone()).unwrap();
+            }
+        }
+
+        let signature = sessions[0].finalize().unwrap();
+        for session in &sessions[1..] {
+            assert_eq!(session.finalize().unwrap(), signature);
+        }
+
+        signature
+    }
+
+    #[test]
+    fn test_key_aggregation_is_order_independent() {
+        let (private_keys, key_agg) = signers(3);
+        let mut public_keys = private_keys
+            .iter()
+            .map(|key| get_public_key_from_private_key(key, true).unwrap())
+            .collect::<Vec<_>>();
+        public_keys.reverse();
+
+        let other = KeyAggContext::new(&public_keys).unwrap();
+        assert_eq!(other.aggregate_public_key(), key_agg.aggregate_public_key());
+        assert_eq!(other.address().len(), 40);
+    }
+
+    #[test]
+    fn test_sign() {
+        let (private_keys, key_agg) = signers(3);
+        let mut sessions = private_keys
+            .iter()
+            .map(|key| Session::new(key_agg.clone(), key, b"hello zilliqa").unwrap())
+            .collect::<Vec<_>>();
+
+        let signature = run(&mut sessions);
+        assert!(verify(
+            b"hello zilliqa",
+            &key_agg.aggregate_public_key(),
+            &signature
+        ));
+        assert!(matches!(
+            sessions[0].partial_sign(),
+            Err(MusigError::NonceUsed)
+        ));
+    }
+
+    #[test]
+    fn test_sign_transaction() {
+        let (private_keys, key_agg) = signers(2);
+        let mut tx = test_support::payment();
+
+        let mut sessions = private_keys
+            .iter()
+            .map(|key| Session::for_transaction(key_agg.clone(), key, &mut tx).unwrap())
+            .collect::<Vec<_>>();
+        let signature = run(&mut sessions);
+
+        assert!(verify(
+            &encode_transaction_proto(&tx).unwrap(),
+            &hex::decode(&tx.sender_pub_key).unwrap(),
+            &signature
+        ));
+    }
+
+    #[test]
+    fn test_rejects_invalid_partial_signature() {
+        let (private_keys, key_agg) = signers(2);
+        let mut sessions = private_keys
+            .iter()
+            .map(|key| Session::new(key_agg.clone(), key, b"hello zilliqa").unwrap())
+            .collect::<Vec<_>>();
+
+        let nonces = sessions
+            .iter()
+            .map(|s| s.public_nonce())
+            .collect::<Vec<_>>();
+        for session in sessions.iter_mut() {
+            assert!(matches!(
+                session.partial_sign(),
+                Err(MusigError::MissingNonces(1))
+            ));
+            let own = session.public_nonce();
+            for nonce in nonces.iter().filter(|nonce| **nonce != own) {
+                session.add_nonce(nonce.clone()).unwrap();
+            }
+        }
+
+        let mut partial = sessions[1].partial_sign().unwrap();
+        partial.s = hex::encode(to_scalar_bytes(&BigUint::from(42u32)));
+        assert!(matches!(
+            sessions[0].add_partial_signature(partial),
+            Err(MusigError::InvalidPartialSignature(_))
+        ));
+
+        let json = serde_json::to_string(&nonces[0]).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PublicNonce>(&json).unwrap(),
+            nonces[0]
+        );
+    }
+
+    #[test]
+    fn test_rejects_own_and_duplicate_nonces() {
+        let (private_keys, key_agg) = signers(2);
+        let mut sessions = private_keys
+            .iter()
+            .map(|key| Session::new(key_agg.clone(), key, b"hello zilliqa").unwrap())
+            .collect::<Vec<_>>();
+
+        let mut forged = sessions[1].public_nonce();
+        forged.public_key = sessions[0].public_nonce().public_key;
+        assert!(matches!(
+            sessions[0].add_nonce(forged),
+            Err(MusigError::OwnNonce)
+        ));
+
+        let nonce = sessions[1].public_nonce();
+        sessions[0].add_nonce(nonce.clone()).unwrap();
+        let mut replaced = sessions[0].public_nonce();
+        replaced.public_key = nonce.public_key;
+        assert!(matches!(
+            sessions[0].add_nonce(replaced),
+            Err(MusigError::DuplicateNonce(_))
+        ));
+
+        // The original nonces still produce a valid partial signature.
+        let partial = sessions[0].partial_sign().unwrap();
+        let nonce = sessions[0].public_nonce();
+        sessions[1].add_nonce(nonce).unwrap();
+        sessions[1].add_partial_signature(partial).unwrap();
+    }
+}
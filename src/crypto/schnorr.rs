@@ -0,0 +1,189 @@
+use std::error::Error;
+
+use num_bigint::BigUint;
+use secp256k1::constants::CURVE_ORDER;
+use secp256k1::rand::rngs::OsRng;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+use crate::util::sha_256;
+
+/// A Schnorr signature as used by Zilliqa, see
+/// https://github.com/Zilliqa/Zilliqa/blob/master/src/libCrypto/Schnorr.cpp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+}
+
+impl Signature {
+    pub fn from_hex(signature: &str) -> Result<Self, Box<dyn Error>> {
+        let bytes = hex::decode(signature.trim_start_matches("0x"))?;
+
+        if bytes.len() != 64 {
+            return Err(String::from("expected signature to be 64 bytes").into());
+        }
+
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&bytes[..32]);
+        s.copy_from_slice(&bytes[32..]);
+
+        Ok(Self { r, s })
+    }
+
+    pub fn to_hex(&self) -> String {
+        format!("{}{}", hex::encode(self.r), hex::encode(self.s))
+    }
+}
+
+pub fn sign(message: &[u8], private_key: &[u8]) -> Result<Signature, Box<dyn Error>> {
+    let secret_key = SecretKey::from_slice(private_key)?;
+    let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key).serialize();
+    let mut rng = OsRng::new()?;
+
+    loop {
+        let k = SecretKey::new(&mut rng);
+
+        if let Some(signature) = sign_with_nonce(message, &secret_key, &public_key, &k) {
+            return Ok(signature);
+        }
+    }
+}
+
+pub fn verify(message: &[u8], public_key: &[u8], signature: &Signature) -> bool {
+    let n = curve_order();
+    let r = BigUint::from_bytes_be(&signature.r);
+    let s = BigUint::from_bytes_be(&signature.s);
+
+    if r == BigUint::default() || r >= n || s == BigUint::default() || s >= n {
+        return false;
+    }
+
+    let public_key = match PublicKey::from_slice(public_key) {
+        Ok(public_key) => public_key,
+        Err(_) => return false,
+    };
+
+    // Q = sG + rP
+    let context = Secp256k1::new();
+    let s_g = match SecretKey::from_slice(&signature.s) {
+        Ok(s) => PublicKey::from_secret_key(&context, &s),
+        Err(_) => return false,
+    };
+
+    let mut r_p = public_key;
+    if r_p.mul_assign(&context, &signature.r).is_err() {
+        return false;
+    }
+
+    match s_g.combine(&r_p) {
+        Ok(q) => challenge(&q, &public_key.serialize(), message) == r,
+        Err(_) => false,
+    }
+}
+
+/// Signs an off-chain message. The message is hashed with SHA-256 before signing, as wallets
+/// like ZilPay do, so that it can never be mistaken for a transaction payload.
+pub fn sign_message(message: &str, private_key: &[u8]) -> Result<Signature, Box<dyn Error>> {
+    sign(&sha_256(message.as_bytes()), private_key)
+}
+
+pub fn verify_message(message: &str, public_key: &[u8], signature: &Signature) -> bool {
+    verify(&sha_256(message.as_bytes()), public_key, signature)
+}
+
+fn sign_with_nonce(
+    message: &[u8],
+    secret_key: &SecretKey,
+    public_key: &[u8],
+    k: &SecretKey,
+) -> Option<Signature> {
+    let n = curve_order();
+    let q = PublicKey::from_secret_key(&Secp256k1::new(), k);
+
+    // r = H(Q || pubkey || message)
+    let r = challenge(&q, public_key, message);
+    if r == BigUint::default() {
+        return None;
+    }
+
+    // s = k - r * privkey
+    let x = BigUint::from_bytes_be(&secret_key.secret_bytes());
+    let k = BigUint::from_bytes_be(&k.secret_bytes());
+    let s = (k + &n - (&r * x) % &n) % &n;
+    if s == BigUint::default() {
+        return None;
+    }
+
+    Some(Signature {
+        r: to_scalar_bytes(&r),
+        s: to_scalar_bytes(&s),
+    })
+}
+
+pub(crate) fn challenge(q: &PublicKey, public_key: &[u8], message: &[u8]) -> BigUint {
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(&q.serialize());
+    buffer.extend_from_slice(public_key);
+    buffer.extend_from_slice(message);
+
+    BigUint::from_bytes_be(&sha_256(&buffer)) % curve_order()
+}
+
+pub(crate) fn curve_order() -> BigUint {
+    BigUint::from_bytes_be(&CURVE_ORDER)
+}
+
+pub(crate) fn to_scalar_bytes(scalar: &BigUint) -> [u8; 32] {
+    let bytes = scalar.to_bytes_be();
+    let mut ret = [0u8; 32];
+    ret[32 - bytes.len()..].copy_from_slice(&bytes);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keytools::get_public_key_from_private_key;
+
+    const PRIVATE_KEY: &str = "24180e6b0c3021aedb8f5a86f75276ee6fc7ff46e67e98e716728326102e91c9";
+
+    #[test]
+    fn test_sign_and_verify() {
+        let private_key = hex::decode(PRIVATE_KEY).unwrap();
+        let public_key = get_public_key_from_private_key(&private_key, true).unwrap();
+
+        let signature = sign(b"hello zilliqa", &private_key).unwrap();
+        assert!(verify(b"hello zilliqa", &public_key, &signature));
+        assert!(!verify(b"hello zilliqa!", &public_key, &signature));
+
+        let other = get_public_key_from_private_key(
+            &hex::decode("af71626e38926401a6d2fd8fdf91c97f785b8fb2b867e7f8a884351e59ee9aa6")
+                .unwrap(),
+            true,
+        )
+        .unwrap();
+        assert!(!verify(b"hello zilliqa", &other, &signature));
+    }
+
+    #[test]
+    fn test_sign_message() {
+        let private_key = hex::decode(PRIVATE_KEY).unwrap();
+        let public_key = get_public_key_from_private_key(&private_key, true).unwrap();
+
+        let signature = sign_message("Hello World", &private_key).unwrap();
+        assert!(verify_message("Hello World", &public_key, &signature));
+        assert!(!verify(b"Hello World", &public_key, &signature));
+    }
+
+    #[test]
+    fn test_signature_hex() {
+        let private_key = hex::decode(PRIVATE_KEY).unwrap();
+        let signature = sign(b"hello zilliqa", &private_key).unwrap();
+
+        let encoded = signature.to_hex();
+        assert_eq!(encoded.len(), 128);
+        assert_eq!(Signature::from_hex(&encoded).unwrap(), signature);
+        assert!(Signature::from_hex("abcd").is_err());
+    }
+}
@@ -0,0 +1,747 @@
+//! An in-process Zilliqa chain for integration tests, enabled with the `devnet` feature.
+//!
+//! [`Devnet`] answers the JSON-RPC API directly as a [`Transport`], keeping balances and nonces,
+//! checking signatures and producing TxBlocks, so providers and middlewares run against it
+//! unchanged:
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use zilliqa_sdk::devnet::Devnet;
+//! use zilliqa_sdk::jsonrpc::{RetryPolicy, RpcClient};
+//! use zilliqa_sdk::provider::blocking::Provider;
+//!
+//! let devnet = Arc::new(Devnet::new());
+//! devnet.fund("9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a", 1_000_000).unwrap();
+//!
+//! let client = RpcClient::with_transport(devnet.clone(), RetryPolicy::no_retry()).unwrap();
+//! let provider = Provider::with_client(client);
+//! let balance = provider.get_balance("9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a").unwrap();
+//! assert_eq!(balance.balance, "1000000");
+//! ```
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::core::Transaction;
+use crate::crypto::schnorr::{verify, Signature};
+use crate::jsonrpc::{self, ErrorCode, RpcError, Transport};
+use crate::keytools::get_address_from_public_key;
+use crate::transaction::encode_transaction_proto;
+use crate::util::{normalize_address, sha_256};
+
+/// The gas charged for a payment, as on the real network.
+pub const TRANSFER_GAS: u64 = 50;
+
+const ZERO_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Error, Debug)]
+pub enum DevnetError {
+    #[error("invalid address {0}")]
+    InvalidAddress(String),
+}
+
+pub type Result<T> = std::result::Result<T, DevnetError>;
+
+type RpcResult<T> = std::result::Result<T, RpcError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockProduction {
+    /// Mines a block for every transaction as soon as it is accepted.
+    Instant,
+    /// Mines a block with all pending transactions once `interval` has passed since the last
+    /// one. Blocks are produced when the devnet is queried, so an idle devnet does not fill up
+    /// with empty blocks.
+    Interval(Duration),
+    /// Only mines blocks when [`Devnet::mine`] is called.
+    Manual,
+}
+
+/// The state of a [`Devnet`] at some point, see [`Devnet::snapshot`].
+#[derive(Clone)]
+pub struct Snapshot {
+    state: State,
+}
+
+#[derive(Debug, Clone, Default)]
+struct AccountState {
+    balance: u128,
+    nonce: u64,
+}
+
+#[derive(Clone)]
+struct PendingTransaction {
+    hash: String,
+    sender: String,
+    to_addr: String,
+    amount: u128,
+    gas_price: u128,
+    json: Value,
+}
+
+#[derive(Clone)]
+struct Block {
+    number: u64,
+    hash: String,
+    prev_hash: String,
+    timestamp: u128,
+    transactions: Vec<String>,
+    gas_used: u64,
+    fees: u128,
+}
+
+#[derive(Clone)]
+struct State {
+    accounts: HashMap<String, AccountState>,
+    pending: Vec<PendingTransaction>,
+    transactions: HashMap<String, Value>,
+    blocks: Vec<Block>,
+    last_block_at: Instant,
+}
+
+/// A single node chain holding payments only. Contract deployments are rejected and contract
+/// methods are not available.
+pub struct Devnet {
+    chain_id: u32,
+    minimum_gas_price: u128,
+    block_production: BlockProduction,
+    state: Mutex<State>,
+}
+
+impl Default for Devnet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Devnet {
+    /// Starts a chain with ID 333 and a genesis block, mining every transaction instantly.
+    pub fn new() -> Self {
+        let mut state = State {
+            accounts: HashMap::new(),
+            pending: Vec::new(),
+            transactions: HashMap::new(),
+            blocks: Vec::new(),
+            last_block_at: Instant::now(),
+        };
+        state.mine();
+
+        Self {
+            chain_id: 333,
+            minimum_gas_price: 2_000_000_000,
+            block_production: BlockProduction::Instant,
+            state: Mutex::new(state),
+        }
+    }
+
+    pub fn chain_id(mut self, chain_id: u32) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    pub fn minimum_gas_price(mut self, minimum_gas_price: u128) -> Self {
+        self.minimum_gas_price = minimum_gas_price;
+        self
+    }
+
+    pub fn block_production(mut self, block_production: BlockProduction) -> Self {
+        self.block_production = block_production;
+        self
+    }
+
+    /// Adds `amount` Qa to the balance of `address`, creating the account if needed.
+    pub fn fund(&self, address: &str, amount: u128) -> Result<()> {
+        let address = normalize_address(address)
+            .map_err(|_| DevnetError::InvalidAddress(address.to_string()))?;
+
+        let mut state = self.state.lock().unwrap();
+        state.accounts.entry(address).or_default().balance += amount;
+
+        Ok(())
+    }
+
+    /// Mines a block with all pending transactions and returns its number.
+    pub fn mine(&self) -> u64 {
+        self.state.lock().unwrap().mine()
+    }
+
+    /// Returns the number of accepted transactions that are not in a block yet.
+    pub fn pending(&self) -> usize {
+        self.state.lock().unwrap().pending.len()
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            state: self.state.lock().unwrap().clone(),
+        }
+    }
+
+    /// Puts accounts, blocks and pending transactions back to how they were at `snapshot`.
+    pub fn restore(&self, snapshot: &Snapshot) {
+        *self.state.lock().unwrap() = snapshot.state.clone();
+    }
+
+    fn handle(&self, request: &Value) -> Value {
+        let method = request["method"].as_str().unwrap_or_default();
+
+        match self.dispatch(method, &request["params"]) {
+            Ok(result) => json!({"id": request["id"], "jsonrpc": "2.0", "result": result}),
+            Err(err) => json!({
+                "id": request["id"],
+                "jsonrpc": "2.0",
+                "error": {"code": err.code, "message": err.message},
+            }),
+        }
+    }
+
+    fn dispatch(&self, method: &str, params: &Value) -> RpcResult<Value> {
+        let mut state = self.state.lock().unwrap();
+
+        if let BlockProduction::Interval(interval) = self.block_production {
+            if state.last_block_at.elapsed() >= interval {
+                state.mine();
+            }
+        }
+
+        match method {
+            "GetNetworkId" => Ok(json!(self.chain_id.to_string())),
+            "GetMinimumGasPrice" => Ok(json!(self.minimum_gas_price.to_string())),
+            "GetNumTxBlocks" => Ok(json!(state.blocks.len().to_string())),
+            "GetLatestTxBlock" => Ok(state.blocks.last().unwrap().to_json()),
+            "GetTxBlock" => Ok(state.block(params)?.to_json()),
+            "GetTransactionsForTxBlock" => {
+                let block = state.block(params)?;
+                if block.transactions.is_empty() {
+                    return Err(error(ErrorCode::MiscError, "TxBlock has no transactions"));
+                }
+
+                Ok(json!([block.transactions]))
+            }
+            "GetTransaction" => {
+                let hash = string_param(params)?
+                    .trim_start_matches("0x")
+                    .to_lowercase();
+                state
+                    .transactions
+                    .get(&hash)
+                    .cloned()
+                    .ok_or_else(|| error(ErrorCode::DatabaseError, "Txn Hash not Present"))
+            }
+            "GetBalance" => {
+                let address = normalize_address(&string_param(params)?)
+                    .map_err(|_| error(ErrorCode::InvalidAddressOrKey, "Invalid address"))?;
+                let account = state.accounts.get(&address).ok_or_else(|| {
+                    error(ErrorCode::InvalidAddressOrKey, "Account is not created")
+                })?;
+
+                Ok(json!({"balance": account.balance.to_string(), "nonce": account.nonce}))
+            }
+            "CreateTransaction" => {
+                let response = self.create_transaction(&mut state, &params[0])?;
+                if self.block_production == BlockProduction::Instant {
+                    state.mine();
+                }
+
+                Ok(response)
+            }
+            _ => Err(error(
+                ErrorCode::MethodNotFound,
+                "METHOD_NOT_FOUND: The method being requested is not available on this server",
+            )),
+        }
+    }
+
+    fn create_transaction(&self, state: &mut State, params: &Value) -> RpcResult<Value> {
+        let params = TransactionParams::deserialize(params)
+            .map_err(|err| error(ErrorCode::InvalidParameter, &err.to_string()))?;
+
+        if params.version >> 16 != self.chain_id {
+            return Err(rejected("CHAIN_ID incorrect"));
+        }
+        if !params.code.is_empty() {
+            return Err(rejected(
+                "Contract deployment is not supported by the devnet",
+            ));
+        }
+
+        let tx = params.to_transaction();
+        let encoded = encode_transaction_proto(&tx)
+            .map_err(|err| error(ErrorCode::InvalidParameter, &err.to_string()))?;
+        let public_key = hex::decode(&params.pub_key)
+            .map_err(|_| error(ErrorCode::InvalidParameter, "Invalid public key"))?;
+        let signature = Signature::from_hex(&params.signature)
+            .map_err(|_| rejected("Invalid signature size"))?;
+        if !verify(&encoded, &public_key, &signature) {
+            return Err(rejected("Signature verification failed"));
+        }
+
+        let amount = parse_u128(&params.amount, "amount")?;
+        let gas_price = parse_u128(&params.gas_price, "gasPrice")?;
+        let gas_limit = parse_u128(&params.gas_limit, "gasLimit")?;
+        if gas_price < self.minimum_gas_price {
+            return Err(rejected(&format!(
+                "GasPrice {} lower than minimum allowable {}",
+                gas_price, self.minimum_gas_price
+            )));
+        }
+        if gas_limit < TRANSFER_GAS.into() {
+            return Err(rejected(&format!(
+                "GasLimit {} lower than minimum {}",
+                gas_limit, TRANSFER_GAS
+            )));
+        }
+
+        let sender = get_address_from_public_key(&public_key)
+            .map_err(|err| error(ErrorCode::InvalidParameter, &err.to_string()))?;
+        let account = state
+            .accounts
+            .get(&sender)
+            .cloned()
+            .ok_or_else(|| rejected("The sender of the txn has no balance"))?;
+
+        // Pending transactions of the sender are applied first, so they count towards both.
+        let queued: Vec<_> = state
+            .pending
+            .iter()
+            .filter(|pending| pending.sender == sender)
+            .collect();
+        let expected_nonce = account.nonce + queued.len() as u64 + 1;
+        if params.nonce != expected_nonce {
+            return Err(rejected(&format!(
+                "Nonce ({}) does not match expected nonce ({})",
+                params.nonce, expected_nonce
+            )));
+        }
+
+        let spent: u128 = queued
+            .iter()
+            .map(|pending| pending.amount + pending.gas_price * u128::from(TRANSFER_GAS))
+            .sum();
+        let cost = gas_price
+            .checked_mul(gas_limit)
+            .and_then(|fee| fee.checked_add(amount))
+            .and_then(|cost| cost.checked_add(spent));
+        if cost.filter(|cost| *cost <= account.balance).is_none() {
+            return Err(rejected("Insufficient balance"));
+        }
+
+        let hash = hex::encode(sha_256(&encoded));
+        let mut json = serde_json::to_value(&tx).unwrap();
+        json["ID"] = json!(hash);
+        json["senderPubKey"] = json!(format!("0x{}", params.pub_key.to_uppercase()));
+        json["signature"] = json!(format!("0x{}", params.signature.to_uppercase()));
+
+        state.pending.push(PendingTransaction {
+            hash: hash.clone(),
+            sender,
+            to_addr: normalize_address(&params.to_addr).unwrap(),
+            amount,
+            gas_price,
+            json,
+        });
+
+        Ok(json!({"Info": "Non-contract txn, sent to shard", "TranID": hash}))
+    }
+}
+
+#[async_trait]
+impl Transport for Devnet {
+    async fn send(&self, request: Value) -> jsonrpc::Result<Value> {
+        Ok(match &request {
+            Value::Array(requests) => Value::Array(
+                requests
+                    .iter()
+                    .map(|request| self.handle(request))
+                    .collect(),
+            ),
+            request => self.handle(request),
+        })
+    }
+}
+
+impl State {
+    fn mine(&mut self) -> u64 {
+        let number = self.blocks.len() as u64;
+        let mut block = Block {
+            number,
+            hash: String::new(),
+            prev_hash: self
+                .blocks
+                .last()
+                .map_or_else(|| ZERO_HASH.to_string(), |block| block.hash.clone()),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_micros(),
+            transactions: Vec::new(),
+            gas_used: 0,
+            fees: 0,
+        };
+
+        for mut tx in mem::take(&mut self.pending) {
+            let fee = tx.gas_price * u128::from(TRANSFER_GAS);
+
+            let sender = self.accounts.get_mut(&tx.sender).unwrap();
+            sender.balance -= tx.amount + fee;
+            sender.nonce += 1;
+            self.accounts.entry(tx.to_addr).or_default().balance += tx.amount;
+
+            tx.json["receipt"] = json!({
+                "cumulative_gas": TRANSFER_GAS.to_string(),
+                "epoch_num": number.to_string(),
+                "success": true,
+            });
+            self.transactions.insert(tx.hash.clone(), tx.json);
+
+            block.transactions.push(tx.hash);
+            block.gas_used += TRANSFER_GAS;
+            block.fees += fee;
+        }
+
+        let mut preimage = hex::decode(&block.prev_hash).unwrap();
+        preimage.extend_from_slice(&number.to_be_bytes());
+        preimage.extend_from_slice(&block.timestamp.to_be_bytes());
+        for hash in &block.transactions {
+            preimage.extend_from_slice(&hex::decode(hash).unwrap());
+        }
+        block.hash = hex::encode(sha_256(&preimage));
+
+        self.blocks.push(block);
+        self.last_block_at = Instant::now();
+
+        number
+    }
+
+    fn block(&self, params: &Value) -> RpcResult<&Block> {
+        string_param(params)?
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| self.blocks.get(number))
+            .ok_or_else(|| error(ErrorCode::InvalidParameter, "TxBlock does not exist"))
+    }
+}
+
+impl Block {
+    fn to_json(&self) -> Value {
+        let micro_blocks: Vec<Value> = if self.transactions.is_empty() {
+            Vec::new()
+        } else {
+            vec![json!({
+                "MicroBlockHash": self.hash,
+                "MicroBlockShardId": 0,
+                "MicroBlockTxnRootHash": ZERO_HASH,
+            })]
+        };
+
+        json!({
+            "body": {
+                "BlockHash": self.hash,
+                "HeaderSign": "",
+                "MicroBlockInfos": micro_blocks,
+            },
+            "header": {
+                "BlockNum": self.number.to_string(),
+                "DSBlockNum": (self.number / 100).to_string(),
+                "GasLimit": "30000000",
+                "GasUsed": self.gas_used.to_string(),
+                "MbInfoHash": ZERO_HASH,
+                "MinerPubKey": "0x",
+                "NumMicroBlocks": micro_blocks.len(),
+                "NumPages": 1,
+                "NumTxns": self.transactions.len(),
+                "PrevBlockHash": self.prev_hash,
+                "Rewards": "0",
+                "StateDeltaHash": ZERO_HASH,
+                "StateRootHash": ZERO_HASH,
+                "Timestamp": self.timestamp.to_string(),
+                "TxnFees": self.fees.to_string(),
+                "Version": 1,
+            }
+        })
+    }
+}
+
+/// The payload of `CreateTransaction`, as built by the provider.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionParams {
+    version: u32,
+    nonce: u64,
+    to_addr: String,
+    amount: String,
+    pub_key: String,
+    gas_price: String,
+    gas_limit: String,
+    #[serde(default)]
+    code: String,
+    #[serde(default)]
+    data: String,
+    signature: String,
+    #[serde(default)]
+    priority: bool,
+}
+
+impl TransactionParams {
+    fn to_transaction(&self) -> Transaction {
+        Transaction {
+            id: None,
+            amount: self.amount.clone(),
+            code: Some(self.code.clone()).filter(|code| !code.is_empty()),
+            data: Some(self.data.clone()).filter(|data| !data.is_empty()),
+            gas_limit: self.gas_limit.clone(),
+            gas_price: self.gas_price.clone(),
+            nonce: Some(self.nonce.to_string()),
+            receipt: None,
+            sender_pub_key: self.pub_key.clone(),
+            signature: Some(self.signature.clone()),
+            to_addr: self.to_addr.clone(),
+            version: self.version.to_string(),
+            priority: self.priority,
+        }
+    }
+}
+
+fn string_param(params: &Value) -> RpcResult<String> {
+    params[0].as_str().map(str::to_string).ok_or_else(|| {
+        error(
+            ErrorCode::InvalidParams,
+            "INVALID_PARAMS: Invalid method parameters (invalid name and/or type) recognised",
+        )
+    })
+}
+
+fn parse_u128(value: &str, name: &str) -> RpcResult<u128> {
+    value
+        .parse()
+        .map_err(|_| error(ErrorCode::InvalidParameter, &format!("Invalid {}", name)))
+}
+
+fn error(code: ErrorCode, message: &str) -> RpcError {
+    RpcError {
+        code: code.code(),
+        message: message.to_string(),
+        data: None,
+    }
+}
+
+fn rejected(message: &str) -> RpcError {
+    error(ErrorCode::VerifyRejected, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::account::wallet::Wallet;
+    use crate::jsonrpc::{RetryPolicy, RpcClient};
+    use crate::keytools::get_address_from_private_key;
+    use crate::provider::middleware::{
+        GasPriceMiddleware, NonceManagerMiddleware, SignerMiddleware,
+    };
+    use crate::provider::{Middleware, Provider, ProviderError};
+    use crate::test_support;
+    use crate::transaction::sign_transaction;
+    use crate::util::pack;
+
+    const PRIVATE_KEY: &str = "e19d05c5452598e24caad4a0d85a49146f7be089515c905ae6a19e8a578a6930";
+    const FUNDS: u128 = 1_000_000_000_000;
+
+    fn sender() -> String {
+        get_address_from_private_key(&hex::decode(PRIVATE_KEY).unwrap()).unwrap()
+    }
+
+    fn devnet(devnet: Devnet) -> (Arc<Devnet>, Provider) {
+        let devnet = Arc::new(devnet);
+        devnet.fund(&sender(), FUNDS).unwrap();
+
+        let client = RpcClient::with_transport(devnet.clone(), RetryPolicy::no_retry()).unwrap();
+        (devnet, Provider::with_client(client))
+    }
+
+    fn transaction(nonce: u64) -> Transaction {
+        Transaction {
+            sender_pub_key: String::new(),
+            amount: "1000".into(),
+            nonce: Some(nonce.to_string()),
+            ..test_support::payment()
+        }
+    }
+
+    fn signed(mut tx: Transaction) -> Transaction {
+        sign_transaction(&mut tx, &hex::decode(PRIVATE_KEY).unwrap()).unwrap();
+        tx
+    }
+
+    fn rejection(err: ProviderError) -> String {
+        let err = err.rpc_error().unwrap();
+        assert_eq!(err.kind(), ErrorCode::VerifyRejected);
+        err.message.clone()
+    }
+
+    #[tokio::test]
+    async fn test_transfer() {
+        let (devnet, provider) = devnet(Devnet::new());
+        let wallet = Wallet::new();
+        wallet.add_by_private_key(PRIVATE_KEY).unwrap();
+
+        let middleware = NonceManagerMiddleware::new(
+            SignerMiddleware::new(GasPriceMiddleware::new(provider), wallet),
+            &sender(),
+        );
+
+        let mut tx = transaction(0);
+        tx.nonce = None;
+        tx.gas_price = String::new();
+        let first = middleware.send_transaction(tx).await.unwrap();
+        let mut tx = transaction(0);
+        tx.nonce = None;
+        let second = middleware.send_transaction(tx).await.unwrap();
+
+        let receipt = middleware
+            .get_transaction(&second.tran_id)
+            .await
+            .unwrap()
+            .receipt
+            .unwrap();
+        assert!(receipt.success);
+        assert_eq!(receipt.epoch_num, "2");
+        assert_eq!(
+            middleware.get_transactions_for_tx_block("1").await.unwrap(),
+            vec![vec![first.tran_id]]
+        );
+        assert_eq!(
+            middleware
+                .get_latest_tx_block()
+                .await
+                .unwrap()
+                .header
+                .num_txns,
+            1
+        );
+
+        let sender = middleware.get_balance(&sender()).await.unwrap();
+        assert_eq!(sender.nonce, 2);
+        assert_eq!(
+            sender.balance,
+            (FUNDS - 2 * (1000 + 50 * 2_000_000_000)).to_string()
+        );
+        let recipient = middleware
+            .get_balance(test_support::RECIPIENT)
+            .await
+            .unwrap();
+        assert_eq!((recipient.balance.as_str(), recipient.nonce), ("2000", 0));
+        assert_eq!(devnet.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_rejected() {
+        let (_devnet, provider) = devnet(Devnet::new());
+
+        let mut tx = signed(transaction(1));
+        tx.amount = "2000".into();
+        let err = provider.create_transaction(&tx).await.unwrap_err();
+        assert_eq!(rejection(err), "Signature verification failed");
+
+        let err = provider
+            .create_transaction(&signed(transaction(2)))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            rejection(err),
+            "Nonce (2) does not match expected nonce (1)"
+        );
+
+        let mut tx = transaction(1);
+        tx.version = pack(1, 1).to_string();
+        let err = provider.create_transaction(&signed(tx)).await.unwrap_err();
+        assert_eq!(rejection(err), "CHAIN_ID incorrect");
+
+        let mut tx = transaction(1);
+        tx.amount = FUNDS.to_string();
+        let err = provider.create_transaction(&signed(tx)).await.unwrap_err();
+        assert_eq!(rejection(err), "Insufficient balance");
+
+        let mut tx = transaction(1);
+        tx.gas_price = "1".into();
+        let err = provider.create_transaction(&signed(tx)).await.unwrap_err();
+        assert!(rejection(err).starts_with("GasPrice 1 lower"));
+
+        let err = provider
+            .get_balance(test_support::RECIPIENT)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.rpc_error().unwrap().kind(),
+            ErrorCode::InvalidAddressOrKey
+        );
+    }
+
+    #[tokio::test]
+    async fn test_manual_mining_and_snapshot() {
+        let (devnet, provider) = devnet(Devnet::new().block_production(BlockProduction::Manual));
+        let snapshot = devnet.snapshot();
+
+        let response = provider
+            .create_transaction(&signed(transaction(1)))
+            .await
+            .unwrap();
+        provider
+            .create_transaction(&signed(transaction(2)))
+            .await
+            .unwrap();
+        assert_eq!(devnet.pending(), 2);
+
+        let err = provider
+            .get_transaction(&response.tran_id)
+            .await
+            .unwrap_err();
+        assert_eq!(err.rpc_error().unwrap().kind(), ErrorCode::DatabaseError);
+
+        assert_eq!(devnet.mine(), 1);
+        assert!(provider
+            .get_transaction(&response.tran_id)
+            .await
+            .unwrap()
+            .receipt
+            .is_some());
+        assert_eq!(provider.get_balance(&sender()).await.unwrap().nonce, 2);
+
+        devnet.restore(&snapshot);
+        assert!(provider.get_transaction(&response.tran_id).await.is_err());
+        assert_eq!(provider.get_balance(&sender()).await.unwrap().nonce, 0);
+        assert_eq!(
+            provider
+                .get_latest_tx_block()
+                .await
+                .unwrap()
+                .header
+                .block_num,
+            "0"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interval() {
+        let (_devnet, provider) = devnet(
+            Devnet::new().block_production(BlockProduction::Interval(Duration::from_millis(20))),
+        );
+
+        let response = provider
+            .create_transaction(&signed(transaction(1)))
+            .await
+            .unwrap();
+        assert!(provider.get_transaction(&response.tran_id).await.is_err());
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let tx = provider.get_transaction(&response.tran_id).await.unwrap();
+        assert_eq!(tx.receipt.unwrap().epoch_num, "1");
+    }
+}
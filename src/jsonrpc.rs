@@ -1,46 +1,603 @@
-use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
 use serde_json::json;
+use thiserror::Error;
+use tracing::field::Empty;
+use tracing::Instrument;
 
 pub use reqwest;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod builder;
+pub mod cassette;
+pub mod failover;
+mod policy;
+pub(crate) mod telemetry;
+pub mod transport;
+
+pub use self::builder::{BuildError, RpcClientBuilder};
+use self::policy::RateLimiter;
+pub use self::policy::{is_write, PolicyError, RetryClass, RetryPolicy};
+pub use self::transport::Transport;
+
 const JSON_RPC_VERSION: &str = "2.0";
 
-pub type Result<T> = std::result::Result<T, reqwest::Error>;
+/// Error codes returned by Zilliqa nodes, see
+/// https://github.com/Zilliqa/Zilliqa/blob/master/src/libServer/JSONConversion.h.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    InvalidRequest,
+    MethodNotFound,
+    InvalidParams,
+    InternalError,
+    ParseError,
+    MiscError,
+    TypeError,
+    InvalidAddressOrKey,
+    InvalidParameter,
+    DatabaseError,
+    DeserializationError,
+    VerifyError,
+    VerifyRejected,
+    InWarmup,
+    MethodDeprecated,
+    Other(i64),
+}
+
+impl From<i64> for ErrorCode {
+    fn from(code: i64) -> Self {
+        match code {
+            -32600 => Self::InvalidRequest,
+            -32601 => Self::MethodNotFound,
+            -32602 => Self::InvalidParams,
+            -32603 => Self::InternalError,
+            -32700 => Self::ParseError,
+            -1 => Self::MiscError,
+            -3 => Self::TypeError,
+            -5 => Self::InvalidAddressOrKey,
+            -8 => Self::InvalidParameter,
+            -20 => Self::DatabaseError,
+            -22 => Self::DeserializationError,
+            -25 => Self::VerifyError,
+            -26 => Self::VerifyRejected,
+            -28 => Self::InWarmup,
+            -32 => Self::MethodDeprecated,
+            code => Self::Other(code),
+        }
+    }
+}
+
+impl ErrorCode {
+    pub fn code(&self) -> i64 {
+        match self {
+            Self::InvalidRequest => -32600,
+            Self::MethodNotFound => -32601,
+            Self::InvalidParams => -32602,
+            Self::InternalError => -32603,
+            Self::ParseError => -32700,
+            Self::MiscError => -1,
+            Self::TypeError => -3,
+            Self::InvalidAddressOrKey => -5,
+            Self::InvalidParameter => -8,
+            Self::DatabaseError => -20,
+            Self::DeserializationError => -22,
+            Self::VerifyError => -25,
+            Self::VerifyRejected => -26,
+            Self::InWarmup => -28,
+            Self::MethodDeprecated => -32,
+            Self::Other(code) => *code,
+        }
+    }
+}
+
+/// The `error` object of a JSON-RPC response.
+#[derive(Error, Debug, Clone, PartialEq, Deserialize)]
+#[error("{message} (code {code})")]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<serde_json::Value>,
+}
+
+impl RpcError {
+    pub fn kind(&self) -> ErrorCode {
+        self.code.into()
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+    #[error("request timed out after {0:?}")]
+    Timeout(Duration),
+    /// A failure of a [`Transport`] other than [`transport::Http`].
+    #[error(transparent)]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The outcome of a single call within a batch.
+pub type BatchResult = std::result::Result<serde_json::Value, RpcError>;
 
 pub struct RpcClient {
-    client: reqwest::blocking::Client,
-    host: String,
+    transport: Box<dyn Transport>,
+    endpoint: Option<String>,
+    next_id: AtomicU64,
+    policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
 }
 
 impl RpcClient {
     pub fn new(host: String) -> Self {
-        let client =
-            tokio::task::block_in_place(move || reqwest::blocking::Client::builder().build())
-                .expect("build client");
+        Self::with_valid_policy(transport::Http::new(host), RetryPolicy::default())
+    }
+
+    /// Fails if `policy` does not pass [`RetryPolicy::validate`].
+    pub fn with_policy(
+        host: String,
+        policy: RetryPolicy,
+    ) -> std::result::Result<Self, PolicyError> {
+        Self::with_transport(transport::Http::new(host), policy)
+    }
+
+    /// Starts configuring a client for `host` with custom headers, authentication, proxy, TLS
+    /// or connection pool settings.
+    pub fn builder(host: String) -> RpcClientBuilder {
+        RpcClientBuilder::new(host)
+    }
+
+    /// Fails if `policy` does not pass [`RetryPolicy::validate`].
+    pub fn with_transport<T: Transport + 'static>(
+        transport: T,
+        policy: RetryPolicy,
+    ) -> std::result::Result<Self, PolicyError> {
+        policy.validate()?;
+        Ok(Self::with_valid_policy(transport, policy))
+    }
 
-        Self { client, host }
+    fn with_valid_policy<T: Transport + 'static>(transport: T, policy: RetryPolicy) -> Self {
+        Self {
+            endpoint: transport.endpoint(),
+            transport: Box::new(transport),
+            next_id: AtomicU64::new(1),
+            rate_limiter: policy.requests_per_second.map(RateLimiter::new),
+            policy,
+        }
     }
 
-    pub fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
-        let request_json = json!({
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Returns a fresh request ID, for callers that want to know the ID of a request up front.
+    pub fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    pub async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        self.call_with_id(self.next_id(), method, params).await
+    }
+
+    pub async fn call_with_id(
+        &self,
+        id: u64,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let request = json!({
            "jsonrpc": JSON_RPC_VERSION,
-           "id": 1,
+           "id": id,
            "method": method.to_string(),
            "params": params,
+        });
+
+        let attempt = self.with_retry(is_write(method), || async {
+            let response = self.transport.send(request.clone()).await?;
+            check_response_id(id, &response)?;
+            Ok(parse_response(response)?)
+        });
+
+        self.traced(method, Some(id), attempt).await
+    }
+
+    /// Sends all `calls` as a single JSON-RPC batch. The results are in the same order as
+    /// `calls`, whatever order the node answers in.
+    pub async fn batch(&self, calls: Vec<(&str, serde_json::Value)>) -> Result<Vec<BatchResult>> {
+        let calls = calls
+            .into_iter()
+            .map(|(method, params)| (self.next_id(), method, params))
+            .collect();
+
+        self.batch_with_ids(calls).await
+    }
+
+    pub async fn batch_with_ids(
+        &self,
+        calls: Vec<(u64, &str, serde_json::Value)>,
+    ) -> Result<Vec<BatchResult>> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<u64> = calls.iter().map(|(id, _, _)| *id).collect();
+        let write = calls.iter().any(|(_, method, _)| is_write(method));
+        let request: serde_json::Value = calls
+            .into_iter()
+            .map(|(id, method, params)| {
+                json!({
+                   "jsonrpc": JSON_RPC_VERSION,
+                   "id": id,
+                   "method": method.to_string(),
+                   "params": params,
+                })
+            })
+            .collect();
+
+        let attempt = self.with_retry(write, || async {
+            match_batch_responses(&ids, self.transport.send(request.clone()).await?)
+        });
+
+        self.traced("batch", None, attempt).await
+    }
+
+    /// Sends a raw request, or batch of requests, under this client's policy and returns the raw
+    /// response without looking at it.
+    pub async fn send_raw(&self, request: serde_json::Value) -> Result<serde_json::Value> {
+        let method = request["method"].as_str().unwrap_or("batch").to_string();
+        let id = request["id"].as_u64();
+        let write = match &request {
+            serde_json::Value::Array(requests) => requests.iter().any(is_write_request),
+            request => is_write_request(request),
+        };
+        let attempt = self.with_retry(write, || self.transport.send(request.clone()));
+
+        self.traced(&method, id, attempt).await
+    }
+
+    async fn traced<T>(
+        &self,
+        method: &str,
+        id: Option<u64>,
+        attempt: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let span = tracing::debug_span!(
+            "rpc_call",
+            method,
+            id,
+            endpoint = self.endpoint.as_deref(),
+            duration_ms = Empty,
+            outcome = Empty,
+            error_code = Empty,
+        );
+
+        let started = Instant::now();
+        let result = attempt.instrument(span.clone()).await;
+        telemetry::finish(&span, method, started.elapsed(), &result);
+
+        result
+    }
+
+    fn should_retry(&self, write: bool, err: &Error) -> bool {
+        if write {
+            self.policy.should_retry_write(err)
+        } else {
+            self.policy.should_retry(err)
+        }
+    }
+
+    /// Runs `attempt` under the retry policy. A `write` is only retried as far as
+    /// [`RetryPolicy::should_retry_write`] allows.
+    async fn with_retry<T, F, Fut>(&self, write: bool, mut attempt: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retry = 0;
+
+        loop {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+
+            let result = tokio::time::timeout(self.policy.timeout, attempt())
+                .await
+                .unwrap_or(Err(Error::Timeout(self.policy.timeout)));
+
+            match result {
+                Err(err) if retry < self.policy.max_retries && self.should_retry(write, &err) => {
+                    let backoff = self.policy.backoff(retry);
+                    telemetry::retry(&err, retry, backoff);
+                    tokio::time::sleep(backoff).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+fn is_write_request(request: &serde_json::Value) -> bool {
+    request["method"].as_str().is_some_and(is_write)
+}
+
+fn parse_response(mut json: serde_json::Value) -> BatchResult {
+    if let Some(error) = json.get_mut("error").filter(|error| !error.is_null()) {
+        let error = RpcError::deserialize(error.take()).unwrap_or_else(|err| RpcError {
+            code: ErrorCode::InternalError.code(),
+            message: format!("malformed error object: {}", err),
+            data: None,
+        });
+        return Err(error);
+    }
+
+    Ok(json["result"].take())
+}
+
+/// Rejects a response to another request, which means the transport mixed up its responses. A
+/// missing or null `id` is accepted, as nodes send those for requests they could not parse.
+fn check_response_id(id: u64, response: &serde_json::Value) -> Result<()> {
+    match response.get("id") {
+        Some(response_id) if !response_id.is_null() && *response_id != json!(id) => {
+            Err(Error::Transport(
+                format!(
+                    "response id {} does not match request id {}",
+                    response_id, id
+                )
+                .into(),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn match_batch_responses(ids: &[u64], response: serde_json::Value) -> Result<Vec<BatchResult>> {
+    // A node that rejects the batch as a whole answers with a single error object.
+    let responses = match response {
+        serde_json::Value::Array(responses) => responses,
+        response => {
+            let err = parse_response(response).err().unwrap_or_else(|| RpcError {
+                code: ErrorCode::InternalError.code(),
+                message: String::from("expected an array of responses"),
+                data: None,
+            });
+            return Err(err.into());
+        }
+    };
+
+    let mut by_id: HashMap<u64, serde_json::Value> = responses
+        .into_iter()
+        .filter_map(|response| Some((response["id"].as_u64()?, response)))
+        .collect();
+
+    Ok(ids
+        .iter()
+        .map(|id| match by_id.remove(id) {
+            Some(response) => parse_response(response),
+            None => Err(RpcError {
+                code: ErrorCode::InternalError.code(),
+                message: format!("missing response for request {}", id),
+                data: None,
+            }),
         })
-        .to_string();
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_call_on_current_thread_runtime() {
+        let client = RpcClient::new("http://127.0.0.1:1".into());
+        assert!(client
+            .call("GetNetworkId", serde_json::Value::Null)
+            .await
+            .is_err());
+    }
+
+    /// Answers each connection with the next canned HTTP response, then stops accepting.
+    async fn serve(responses: Vec<&'static str>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
 
-        let response = tokio::task::block_in_place(move || {
-            self.client
-                .post(&self.host)
-                .header(CONTENT_TYPE, "application/json")
-                .header(ACCEPT, "application/json")
-                .body(request_json)
-                .send()
-        })?;
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buffer = [0u8; 4096];
+                let _ = socket.read(&mut buffer).await.unwrap();
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
 
-        let mut json = tokio::task::block_in_place(move || response.json::<serde_json::Value>())?;
+        host
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_too_many_requests() {
+        let too_many_requests =
+            "HTTP/1.1 429 Too Many Requests\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+        let ok = concat!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 44\r\n",
+            "connection: close\r\n\r\n",
+            r#"{"id":1,"jsonrpc":"2.0","result":"20000000"}"#
+        );
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        };
+
+        let host = serve(vec![too_many_requests, too_many_requests, ok]).await;
+        let client = RpcClient::with_policy(host, policy.clone()).unwrap();
+        let result = client
+            .call("GetMinimumGasPrice", serde_json::Value::Null)
+            .await;
+        assert_eq!(result.unwrap(), json!("20000000"));
+
+        let host = serve(vec![too_many_requests, ok]).await;
+        let client = RpcClient::with_policy(
+            host,
+            RetryPolicy {
+                max_retries: 0,
+                ..policy
+            },
+        )
+        .unwrap();
+        let err = client
+            .call("GetMinimumGasPrice", serde_json::Value::Null)
+            .await
+            .unwrap_err();
+        assert_eq!(err.retry_class(), RetryClass::Network);
+    }
+
+    #[tokio::test]
+    async fn test_writes_not_retried() {
+        let unavailable =
+            "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+        let too_many_requests =
+            "HTTP/1.1 429 Too Many Requests\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+        let ok = concat!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 40\r\n",
+            "connection: close\r\n\r\n",
+            r#"{"id":1,"jsonrpc":"2.0","result":"abcd"}"#
+        );
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        };
+
+        // The node may have accepted the transaction before failing.
+        let host = serve(vec![unavailable, ok]).await;
+        let client = RpcClient::with_policy(host, policy.clone()).unwrap();
+        let err = client
+            .call("CreateTransaction", serde_json::Value::Null)
+            .await
+            .unwrap_err();
+        assert_eq!(err.retry_class(), RetryClass::Network);
+
+        // The node turned the transaction away.
+        let host = serve(vec![too_many_requests, ok]).await;
+        let client = RpcClient::with_policy(host, policy.clone()).unwrap();
+        let result = client
+            .call("CreateTransaction", serde_json::Value::Null)
+            .await;
+        assert_eq!(result.unwrap(), json!("abcd"));
+
+        let host = serve(vec![unavailable, ok]).await;
+        let client = RpcClient::with_policy(
+            host,
+            RetryPolicy {
+                retry_writes: true,
+                ..policy
+            },
+        )
+        .unwrap();
+        let result = client
+            .call("CreateTransaction", serde_json::Value::Null)
+            .await;
+        assert_eq!(result.unwrap(), json!("abcd"));
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+        let policy = RetryPolicy {
+            timeout: Duration::from_millis(50),
+            ..RetryPolicy::no_retry()
+        };
+
+        let client = RpcClient::with_policy(host, policy).unwrap();
+        let result = client.call("GetNetworkId", serde_json::Value::Null).await;
+        assert!(matches!(result, Err(Error::Timeout(_))));
+        drop(listener);
+    }
+
+    #[test]
+    fn test_parse_error_response() {
+        let response = json!({
+            "id": 1,
+            "jsonrpc": "2.0",
+            "error": {
+                "code": -5,
+                "data": null,
+                "message": "Account is not created"
+            }
+        });
+
+        match parse_response(response) {
+            Err(err) => {
+                assert_eq!(err.kind(), ErrorCode::InvalidAddressOrKey);
+                assert_eq!(err.message, "Account is not created");
+                assert_eq!(err.to_string(), "Account is not created (code -5)");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let response = json!({"id": 1, "jsonrpc": "2.0", "result": "2000000000"});
+        assert_eq!(parse_response(response).unwrap(), json!("2000000000"));
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_response_id() {
+        let transport = transport::Memory::new(
+            |request| json!({"id": request["id"].as_u64().unwrap() + 1, "jsonrpc": "2.0", "result": "1"}),
+        );
+        let client = RpcClient::with_transport(transport, RetryPolicy::no_retry()).unwrap();
+
+        let err = client
+            .call("GetNetworkId", serde_json::Value::Null)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Transport(_)));
+        assert_eq!(err.to_string(), "response id 2 does not match request id 1");
+
+        assert!(check_response_id(1, &json!({"id": null, "error": {}})).is_ok());
+    }
+
+    #[test]
+    fn test_match_batch_responses() {
+        let response = json!([
+            {"id": 3, "jsonrpc": "2.0", "result": "three"},
+            {"id": 1, "jsonrpc": "2.0", "result": "one"},
+            {"id": 2, "jsonrpc": "2.0", "error": {"code": -5, "message": "Account is not created"}},
+        ]);
+
+        let results = match_batch_responses(&[1, 2, 3, 4], response).unwrap();
+        assert_eq!(results[0], Ok(json!("one")));
+        assert_eq!(
+            results[1].as_ref().unwrap_err().kind(),
+            ErrorCode::InvalidAddressOrKey
+        );
+        assert_eq!(results[2], Ok(json!("three")));
+        assert_eq!(
+            results[3].as_ref().unwrap_err().kind(),
+            ErrorCode::InternalError
+        );
+
+        let response = json!({"id": null, "jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid request"}});
+        assert!(matches!(
+            match_batch_responses(&[1], response),
+            Err(Error::Rpc(err)) if err.kind() == ErrorCode::InvalidRequest
+        ));
+    }
 
-        Ok(json["result"].take())
+    #[test]
+    fn test_error_code() {
+        assert_eq!(ErrorCode::from(-32601), ErrorCode::MethodNotFound);
+        assert_eq!(ErrorCode::from(-42), ErrorCode::Other(-42));
+        assert_eq!(ErrorCode::VerifyRejected.code(), -26);
+        assert_eq!(ErrorCode::Other(-42).code(), -42);
     }
 }
@@ -0,0 +1,87 @@
+//! A blocking [`RpcClient`](super::RpcClient) for use outside of an async runtime.
+
+use tokio::runtime::{Builder, Runtime};
+
+use super::{BatchResult, PolicyError, Result, RetryPolicy, Transport};
+
+/// Runs the async client on its own single threaded runtime. Like `reqwest::blocking`, it must
+/// not be used from within an async context.
+pub struct RpcClient {
+    inner: super::RpcClient,
+    runtime: Runtime,
+}
+
+impl RpcClient {
+    pub fn new(host: String) -> Self {
+        Self {
+            inner: super::RpcClient::new(host),
+            runtime: new_runtime(),
+        }
+    }
+
+    /// Fails if `policy` does not pass [`RetryPolicy::validate`].
+    pub fn with_policy(
+        host: String,
+        policy: RetryPolicy,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(super::RpcClient::with_policy(host, policy)?.into())
+    }
+
+    /// Fails if `policy` does not pass [`RetryPolicy::validate`].
+    pub fn with_transport<T: Transport + 'static>(
+        transport: T,
+        policy: RetryPolicy,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(super::RpcClient::with_transport(transport, policy)?.into())
+    }
+
+    pub fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        self.runtime.block_on(self.inner.call(method, params))
+    }
+
+    pub fn batch(&self, calls: Vec<(&str, serde_json::Value)>) -> Result<Vec<BatchResult>> {
+        self.runtime.block_on(self.inner.batch(calls))
+    }
+}
+
+/// Wraps an async client, e.g. one made with [`RpcClient::builder`](super::RpcClient::builder).
+impl From<super::RpcClient> for RpcClient {
+    fn from(inner: super::RpcClient) -> Self {
+        Self {
+            inner,
+            runtime: new_runtime(),
+        }
+    }
+}
+
+pub(crate) fn new_runtime() -> Runtime {
+    Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("build runtime")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_outside_runtime() {
+        let client = RpcClient::new("http://127.0.0.1:1".into());
+        assert!(client
+            .call("GetNetworkId", serde_json::Value::Null)
+            .is_err());
+    }
+
+    #[test]
+    fn test_from_builder() {
+        let client: RpcClient = super::super::RpcClient::builder("http://127.0.0.1:1".into())
+            .policy(RetryPolicy::no_retry())
+            .build()
+            .unwrap()
+            .into();
+        assert!(client
+            .call("GetNetworkId", serde_json::Value::Null)
+            .is_err());
+    }
+}
@@ -0,0 +1,301 @@
+//! Recording interactions with a node to a cassette file, and replaying them in tests.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{Error, Result, Transport};
+
+/// A request and the response it got, without the request ID.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    pub params: Value,
+    pub response: Value,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+/// Forwards requests to `inner` and records every request/response pair. Batches are recorded
+/// call by call, so they can be replayed one by one and vice versa.
+///
+/// The cassette file is written by [`Recorder::save`] and when the recorder is dropped.
+pub struct Recorder<T> {
+    inner: T,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+impl<T> Recorder<T> {
+    /// Starts a new cassette at `path`, replacing any existing one.
+    pub fn new(inner: T, path: impl AsRef<Path>) -> io::Result<Self> {
+        let recorder = Self {
+            inner,
+            path: path.as_ref().to_path_buf(),
+            cassette: Mutex::new(Cassette::default()),
+        };
+        recorder.save()?;
+
+        Ok(recorder)
+    }
+
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.cassette.lock().unwrap().interactions.clone()
+    }
+
+    /// Writes everything recorded so far to the cassette file. The file is replaced atomically,
+    /// so it never holds half a cassette.
+    pub fn save(&self) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(&*self.cassette.lock().unwrap())?;
+
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(format!(".{}.tmp", uuid::Uuid::new_v4()));
+        let tmp = PathBuf::from(tmp);
+
+        let result = fs::write(&tmp, json).and_then(|_| fs::rename(&tmp, &self.path));
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+
+        result
+    }
+}
+
+impl<T> Drop for Recorder<T> {
+    fn drop(&mut self) {
+        if let Err(err) = self.save() {
+            tracing::warn!(path = %self.path.display(), %err, "failed to save cassette");
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for Recorder<T> {
+    async fn send(&self, request: Value) -> Result<Value> {
+        let response = self.inner.send(request.clone()).await?;
+
+        let mut cassette = self.cassette.lock().unwrap();
+        match (&request, &response) {
+            (Value::Array(requests), Value::Array(responses)) => {
+                for request in requests {
+                    if let Some(response) = responses.iter().find(|r| r["id"] == request["id"]) {
+                        cassette.interactions.push(interaction(request, response));
+                    }
+                }
+            }
+            (request, response) => cassette.interactions.push(interaction(request, response)),
+        }
+
+        Ok(response)
+    }
+
+    fn endpoint(&self) -> Option<String> {
+        self.inner.endpoint()
+    }
+}
+
+fn interaction(request: &Value, response: &Value) -> Interaction {
+    let mut response = response.clone();
+    if let Some(response) = response.as_object_mut() {
+        response.remove("id");
+    }
+
+    Interaction {
+        method: request["method"].as_str().unwrap_or_default().to_string(),
+        params: request["params"].clone(),
+        response,
+    }
+}
+
+/// Answers requests from a cassette written by a [`Recorder`], without any network access.
+///
+/// Each recorded interaction is used once, in recording order, so that repeated calls like
+/// polling a balance replay the same sequence of answers. A request without a matching
+/// interaction left fails with a transport error, as it means the code under test changed its
+/// behaviour.
+pub struct Replayer {
+    interactions: Vec<Interaction>,
+    used: Mutex<Vec<bool>>,
+}
+
+impl Replayer {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let cassette: Cassette = serde_json::from_slice(&fs::read(path)?)?;
+        Ok(Self::new(cassette.interactions))
+    }
+
+    pub fn new(interactions: Vec<Interaction>) -> Self {
+        Self {
+            used: Mutex::new(vec![false; interactions.len()]),
+            interactions,
+        }
+    }
+
+    /// Returns the number of interactions that have not been replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.used
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|used| !**used)
+            .count()
+    }
+
+    /// Answers `requests`, using up their interactions only if all of them have one left.
+    fn replay(&self, requests: &[Value]) -> Result<Vec<Value>> {
+        let mut used = self.used.lock().unwrap();
+        let mut indices = Vec::with_capacity(requests.len());
+
+        for request in requests {
+            let method = request["method"].as_str().unwrap_or_default();
+            let params = &request["params"];
+
+            let index = self
+                .interactions
+                .iter()
+                .enumerate()
+                .position(|(i, interaction)| {
+                    !used[i]
+                        && !indices.contains(&i)
+                        && interaction.method == method
+                        && interaction.params == *params
+                })
+                .ok_or_else(|| {
+                    Error::Transport(
+                        format!(
+                            "no recorded interaction left for {} with params {}",
+                            method, params
+                        )
+                        .into(),
+                    )
+                })?;
+            indices.push(index);
+        }
+
+        Ok(requests
+            .iter()
+            .zip(indices)
+            .map(|(request, index)| {
+                used[index] = true;
+
+                let mut response = self.interactions[index].response.clone();
+                if let Some(object) = response.as_object_mut() {
+                    object.insert("id".to_string(), request["id"].clone());
+                }
+                response
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl Transport for Replayer {
+    async fn send(&self, request: Value) -> Result<Value> {
+        match request {
+            Value::Array(requests) => self.replay(&requests).map(Value::Array),
+            request => Ok(self.replay(&[request])?.remove(0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::jsonrpc::transport::Memory;
+    use crate::jsonrpc::{RetryPolicy, RpcClient};
+
+    fn answer(request: &Value) -> Value {
+        let result = match request["method"].as_str().unwrap() {
+            "GetBalance" => json!({"balance": "100", "nonce": request["params"][0]}),
+            _ => json!("2000000000"),
+        };
+
+        json!({"id": request["id"], "jsonrpc": "2.0", "result": result})
+    }
+
+    fn node() -> Memory<impl Fn(Value) -> Value + Send + Sync> {
+        Memory::new(|request| match request {
+            Value::Array(requests) => Value::Array(requests.iter().map(answer).collect()),
+            request => answer(&request),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let path =
+            std::env::temp_dir().join(format!("zilliqa-cassette-{}.json", uuid::Uuid::new_v4()));
+
+        let recorder = Recorder::new(node(), &path).unwrap();
+        let client = RpcClient::with_transport(recorder, RetryPolicy::no_retry()).unwrap();
+        client
+            .call("GetMinimumGasPrice", Value::Null)
+            .await
+            .unwrap();
+        client
+            .batch(vec![("GetBalance", json!([1])), ("GetBalance", json!([2]))])
+            .await
+            .unwrap();
+        assert_eq!(Replayer::load(&path).unwrap().remaining(), 0);
+        drop(client);
+
+        let replayer = Replayer::load(&path).unwrap();
+        assert_eq!(replayer.remaining(), 3);
+        let client = RpcClient::with_transport(replayer, RetryPolicy::no_retry()).unwrap();
+
+        let balance = client.call("GetBalance", json!([2])).await.unwrap();
+        assert_eq!(balance, json!({"balance": "100", "nonce": 2}));
+        let results = client
+            .batch(vec![
+                ("GetMinimumGasPrice", Value::Null),
+                ("GetBalance", json!([1])),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(results[0], Ok(json!("2000000000")));
+        assert_eq!(results[1], Ok(json!({"balance": "100", "nonce": 1})));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_replay_unmatched() {
+        let replayer = Replayer::new(vec![Interaction {
+            method: "GetBalance".into(),
+            params: json!([1]),
+            response: json!({"jsonrpc": "2.0", "result": {"balance": "100", "nonce": 1}}),
+        }]);
+
+        // A batch with an unmatched request uses up none of its interactions.
+        let err = replayer
+            .send(json!([
+                {"id": 1, "method": "GetBalance", "params": [1]},
+                {"id": 2, "method": "GetBalance", "params": [1]},
+            ]))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no recorded interaction left for GetBalance with params [1]"
+        );
+        assert_eq!(replayer.remaining(), 1);
+
+        replayer
+            .send(json!({"id": 3, "method": "GetBalance", "params": [1]}))
+            .await
+            .unwrap();
+        assert!(replayer
+            .send(json!({"id": 4, "method": "GetBalance", "params": [1]}))
+            .await
+            .is_err());
+    }
+}
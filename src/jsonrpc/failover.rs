@@ -0,0 +1,468 @@
+//! Spreading requests over several endpoints.
+//!
+//! [`Failover`] is a [`Transport`] on top of several [`RpcClient`]s, so a provider using it is
+//! built like any other:
+//!
+//! ```no_run
+//! use zilliqa_sdk::jsonrpc::failover::{Failover, Strategy};
+//! use zilliqa_sdk::jsonrpc::{RetryPolicy, RpcClient};
+//! use zilliqa_sdk::provider::Provider;
+//!
+//! # fn main() -> Result<(), zilliqa_sdk::jsonrpc::PolicyError> {
+//! let failover = Failover::new(vec![
+//!     RpcClient::with_policy("https://api.zilliqa.com".into(), RetryPolicy::no_retry())?,
+//!     RpcClient::with_policy("https://zilliqa-api.example.com".into(), RetryPolicy::no_retry())?,
+//! ])
+//! .strategy(Strategy::RoundRobin);
+//! let provider = Provider::with_client(RpcClient::with_transport(failover, RetryPolicy::no_retry())?);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{Error, Result, RetryClass, RpcClient, RpcError, Transport};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Always start with the first healthy endpoint.
+    Priority,
+    /// Start with the next endpoint on every request.
+    RoundRobin,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum FailoverError {
+    #[error("no endpoints configured")]
+    NoEndpoints,
+    #[error("only {agreeing} of {queried} endpoints agreed, {required} required")]
+    NoQuorum {
+        agreeing: usize,
+        queried: usize,
+        required: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointHealth {
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+}
+
+struct Endpoint {
+    client: RpcClient,
+    consecutive_failures: AtomicU32,
+    unhealthy_since: Mutex<Option<Instant>>,
+}
+
+/// Sends each request to one endpoint at a time, moving on to the next one when an endpoint
+/// fails. Endpoints failing `max_failures` times in a row are skipped for `cooldown`, unless no
+/// other endpoint is left.
+///
+/// Transactions only move on to the next endpoint when the failed one provably did not receive
+/// them, e.g. when it refused the connection, so that a transaction is not sent twice.
+///
+/// In quorum mode, requests are sent to `n` endpoints at once and only a response that a
+/// majority of them agree on is accepted. Transactions are never sent in quorum mode.
+pub struct Failover {
+    endpoints: Vec<Endpoint>,
+    strategy: Strategy,
+    quorum: Option<usize>,
+    max_failures: u32,
+    cooldown: Duration,
+    next: AtomicUsize,
+}
+
+impl Failover {
+    pub fn new(clients: Vec<RpcClient>) -> Self {
+        Self {
+            endpoints: clients
+                .into_iter()
+                .map(|client| Endpoint {
+                    client,
+                    consecutive_failures: AtomicU32::new(0),
+                    unhealthy_since: Mutex::new(None),
+                })
+                .collect(),
+            strategy: Strategy::Priority,
+            quorum: None,
+            max_failures: 3,
+            cooldown: Duration::from_secs(30),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Queries `n` endpoints for every read and requires more than `n / 2` of them to agree, even
+    /// when fewer than `n` endpoints are configured.
+    pub fn quorum(mut self, n: usize) -> Self {
+        self.quorum = Some(n);
+        self
+    }
+
+    pub fn max_failures(mut self, max_failures: u32) -> Self {
+        self.max_failures = max_failures;
+        self
+    }
+
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Returns the health of each endpoint, in the order they were given.
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| EndpointHealth {
+                healthy: self.is_healthy(endpoint),
+                consecutive_failures: endpoint.consecutive_failures.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    fn is_healthy(&self, endpoint: &Endpoint) -> bool {
+        match *endpoint.unhealthy_since.lock().unwrap() {
+            Some(since) => since.elapsed() >= self.cooldown,
+            None => true,
+        }
+    }
+
+    fn record(&self, endpoint: &Endpoint, success: bool) {
+        let mut unhealthy_since = endpoint.unhealthy_since.lock().unwrap();
+
+        if success {
+            endpoint.consecutive_failures.store(0, Ordering::Relaxed);
+            *unhealthy_since = None;
+        } else {
+            let failures = endpoint
+                .consecutive_failures
+                .fetch_add(1, Ordering::Relaxed)
+                + 1;
+            if failures >= self.max_failures {
+                *unhealthy_since = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Returns the endpoints to try, healthy ones first.
+    fn order(&self) -> Vec<&Endpoint> {
+        let start = match self.strategy {
+            Strategy::Priority => 0,
+            Strategy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed),
+        };
+
+        let len = self.endpoints.len();
+        let (healthy, unhealthy): (Vec<_>, Vec<_>) = (0..len)
+            .map(|i| &self.endpoints[(start + i) % len])
+            .partition(|endpoint| self.is_healthy(endpoint));
+
+        healthy.into_iter().chain(unhealthy).collect()
+    }
+
+    async fn send_one(&self, request: Value) -> Result<Value> {
+        let write = is_write(&request);
+        let mut last = None;
+
+        for endpoint in self.order() {
+            match endpoint.client.send_raw(request.clone()).await {
+                Ok(response) if !is_node_failure(&response) => {
+                    self.record(endpoint, true);
+                    return Ok(response);
+                }
+                Err(err) if write && !err.is_unsent() => {
+                    self.record(endpoint, false);
+                    return Err(err);
+                }
+                Ok(response) if write => {
+                    self.record(endpoint, false);
+                    return Ok(response);
+                }
+                result => {
+                    self.record(endpoint, false);
+                    last = Some(result);
+                }
+            }
+        }
+
+        last.unwrap_or_else(|| Err(Error::Transport(FailoverError::NoEndpoints.into())))
+    }
+
+    async fn send_quorum(&self, request: Value, n: usize) -> Result<Value> {
+        let endpoints: Vec<_> = self.order().into_iter().take(n).collect();
+        let queried = endpoints.len();
+        let required = n / 2 + 1;
+
+        let results = futures::future::join_all(
+            endpoints
+                .iter()
+                .map(|endpoint| endpoint.client.send_raw(request.clone())),
+        )
+        .await;
+
+        let mut votes: Vec<(Value, usize)> = Vec::new();
+        let mut last_err = None;
+        for (endpoint, result) in endpoints.into_iter().zip(results) {
+            match result {
+                Ok(response) => {
+                    self.record(endpoint, !is_node_failure(&response));
+                    let response = normalize(response);
+                    match votes.iter_mut().find(|(value, _)| *value == response) {
+                        Some((_, count)) => *count += 1,
+                        None => votes.push((response, 1)),
+                    }
+                }
+                Err(err) => {
+                    self.record(endpoint, false);
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        let agreeing = votes.iter().map(|(_, count)| *count).max().unwrap_or(0);
+        if agreeing >= required {
+            let (response, _) = votes
+                .into_iter()
+                .find(|(_, count)| *count == agreeing)
+                .unwrap();
+            return Ok(response);
+        }
+
+        match last_err {
+            Some(err) if agreeing == 0 => Err(err),
+            _ => Err(Error::Transport(
+                FailoverError::NoQuorum {
+                    agreeing,
+                    queried,
+                    required,
+                }
+                .into(),
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for Failover {
+    async fn send(&self, request: Value) -> Result<Value> {
+        match self.quorum {
+            Some(n) if !is_write(&request) => self.send_quorum(request, n.max(1)).await,
+            _ => self.send_one(request).await,
+        }
+    }
+}
+
+/// Whether the node answered with an error that another node may not have.
+fn is_node_failure(response: &Value) -> bool {
+    match response.get("error").filter(|error| !error.is_null()) {
+        Some(error) => RpcError::deserialize(error.clone())
+            .map(|err| Error::Rpc(err).retry_class() == RetryClass::Node)
+            .unwrap_or(false),
+        None => false,
+    }
+}
+
+fn is_write(request: &Value) -> bool {
+    match request {
+        Value::Array(requests) => requests.iter().any(is_write),
+        request => request["method"].as_str().is_some_and(super::is_write),
+    }
+}
+
+/// Puts batch responses in ID order, so that responses can be compared.
+fn normalize(response: Value) -> Value {
+    match response {
+        Value::Array(mut responses) => {
+            responses.sort_by_key(|response| response["id"].as_u64());
+            Value::Array(responses)
+        }
+        response => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::jsonrpc::transport::{Memory, Unsent};
+    use crate::jsonrpc::RetryPolicy;
+
+    struct Down(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl Transport for Down {
+        async fn send(&self, _request: Value) -> Result<Value> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Err(Error::Transport("connection refused".into()))
+        }
+    }
+
+    struct Closed;
+
+    #[async_trait]
+    impl Transport for Closed {
+        async fn send(&self, _request: Value) -> Result<Value> {
+            Err(Unsent::error("connection closed"))
+        }
+    }
+
+    fn answering(result: &'static str) -> RpcClient {
+        let transport = Memory::new(
+            move |request| json!({"id": request["id"], "jsonrpc": "2.0", "result": result}),
+        );
+
+        RpcClient::with_transport(transport, RetryPolicy::no_retry()).unwrap()
+    }
+
+    fn down(calls: &Arc<AtomicUsize>) -> RpcClient {
+        RpcClient::with_transport(Down(calls.clone()), RetryPolicy::no_retry()).unwrap()
+    }
+
+    fn client(failover: Failover) -> RpcClient {
+        RpcClient::with_transport(failover, RetryPolicy::no_retry()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_priority() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let failover = Failover::new(vec![down(&calls), answering("a"), answering("b")]);
+        let client = client(failover.max_failures(2));
+
+        for _ in 0..4 {
+            assert_eq!(client.call("GetNetworkId", Value::Null).await.unwrap(), "a");
+        }
+
+        // The first endpoint is skipped once it failed twice in a row.
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_health() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let failover = Failover::new(vec![down(&calls), answering("a")]).max_failures(1);
+
+        failover
+            .send(json!({"id": 1, "method": "GetNetworkId"}))
+            .await
+            .unwrap();
+        assert_eq!(
+            failover.health(),
+            vec![
+                EndpointHealth {
+                    healthy: false,
+                    consecutive_failures: 1
+                },
+                EndpointHealth {
+                    healthy: true,
+                    consecutive_failures: 0
+                },
+            ]
+        );
+
+        let failover = Failover::new(vec![down(&calls)]);
+        assert!(failover.send(json!({"id": 1})).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_round_robin() {
+        let failover = Failover::new(vec![answering("a"), answering("b")]);
+        let client = client(failover.strategy(Strategy::RoundRobin));
+
+        let mut results = Vec::new();
+        for _ in 0..4 {
+            results.push(client.call("GetNetworkId", Value::Null).await.unwrap());
+        }
+        assert_eq!(results, vec!["a", "b", "a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_quorum() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let failover = Failover::new(vec![answering("a"), down(&calls), answering("a")]).quorum(3);
+        assert_eq!(
+            client(failover)
+                .call("GetBalance", Value::Null)
+                .await
+                .unwrap(),
+            "a"
+        );
+
+        let failover =
+            Failover::new(vec![answering("a"), answering("b"), answering("c")]).quorum(3);
+        let err = client(failover)
+            .call("GetBalance", Value::Null)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "only 1 of 3 endpoints agreed, 2 required");
+
+        // Fewer endpoints than the quorum cannot agree on their own.
+        let failover = Failover::new(vec![answering("a"), answering("a")]).quorum(5);
+        let err = client(failover)
+            .call("GetBalance", Value::Null)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "only 2 of 2 endpoints agreed, 3 required");
+
+        // Transactions go to a single endpoint.
+        let failover =
+            Failover::new(vec![answering("a"), answering("b"), answering("c")]).quorum(3);
+        assert_eq!(
+            client(failover)
+                .call("CreateTransaction", Value::Null)
+                .await
+                .unwrap(),
+            "a"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_failover() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let failover = Failover::new(vec![down(&calls), answering("a")]);
+        assert!(client(failover)
+            .call("CreateTransaction", Value::Null)
+            .await
+            .is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // An IPC or WebSocket endpoint whose connection had closed never saw the transaction.
+        let closed = RpcClient::with_transport(Closed, RetryPolicy::no_retry()).unwrap();
+        let failover = Failover::new(vec![closed, answering("a")]);
+        assert_eq!(
+            client(failover)
+                .call("CreateTransaction", Value::Null)
+                .await
+                .unwrap(),
+            "a"
+        );
+
+        // An endpoint refusing the connection never saw the transaction.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let refused = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let failover = Failover::new(vec![
+            RpcClient::with_policy(refused, RetryPolicy::no_retry()).unwrap(),
+            answering("a"),
+        ]);
+        assert_eq!(
+            client(failover)
+                .call("CreateTransaction", Value::Null)
+                .await
+                .unwrap(),
+            "a"
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use std::time::Duration;
+
+use rand::Rng;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use super::transport::Unsent;
+use super::{Error, ErrorCode};
+
+/// How an [`RpcClient`](super::RpcClient) times out, retries and throttles its requests.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Upper bound for a single attempt, including reading the response.
+    pub timeout: Duration,
+    /// Number of retries after the first attempt.
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Must be at least 1.
+    pub backoff_multiplier: f64,
+    /// Randomizes each backoff between half and all of its value, so that clients that failed
+    /// together do not retry together.
+    pub jitter: bool,
+    /// Whether transient node errors, like a node still warming up, are retried.
+    pub retry_node_errors: bool,
+    /// Client side limit on the number of requests per second, shared by all callers of the
+    /// client. Batches count as one request. Must be positive.
+    pub requests_per_second: Option<f64>,
+    /// Whether requests that are not safe to repeat, like `CreateTransaction`, are retried after
+    /// failures that may have happened after the node received them. When off, they are only
+    /// retried when the node provably did not process them.
+    pub retry_writes: bool,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum PolicyError {
+    #[error("requests per second must be positive, got {0}")]
+    InvalidRate(f64),
+    #[error("backoff multiplier must be at least 1, got {0}")]
+    InvalidBackoffMultiplier(f64),
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            backoff_multiplier: 2.0,
+            jitter: true,
+            retry_node_errors: true,
+            requests_per_second: None,
+            retry_writes: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that tries every request exactly once.
+    pub fn no_retry() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Checks the fields that would otherwise make backoffs or throttling misbehave.
+    pub fn validate(&self) -> Result<(), PolicyError> {
+        if let Some(rate) = self.requests_per_second {
+            if !(rate > 0.0 && rate.is_finite()) {
+                return Err(PolicyError::InvalidRate(rate));
+            }
+        }
+        if !(self.backoff_multiplier >= 1.0 && self.backoff_multiplier.is_finite()) {
+            return Err(PolicyError::InvalidBackoffMultiplier(
+                self.backoff_multiplier,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns how long to wait before retry number `retry`, counting from zero.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let backoff =
+            self.initial_backoff.as_secs_f64() * self.backoff_multiplier.powi(retry as i32);
+        let backoff = backoff.min(self.max_backoff.as_secs_f64());
+
+        if self.jitter {
+            Duration::from_secs_f64(rand::thread_rng().gen_range(backoff / 2.0..=backoff))
+        } else {
+            Duration::from_secs_f64(backoff)
+        }
+    }
+
+    pub fn should_retry(&self, err: &Error) -> bool {
+        match err.retry_class() {
+            RetryClass::Network => true,
+            RetryClass::Node => self.retry_node_errors,
+            RetryClass::NonRetryable => false,
+        }
+    }
+
+    /// Like [`RetryPolicy::should_retry`], for requests containing a [write](is_write).
+    pub fn should_retry_write(&self, err: &Error) -> bool {
+        if self.retry_writes {
+            self.should_retry(err)
+        } else {
+            err.is_unsent()
+        }
+    }
+}
+
+/// Returns whether `method` changes state on the node, so that repeating it is not safe.
+pub fn is_write(method: &str) -> bool {
+    method == "CreateTransaction"
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryClass {
+    /// Timeouts, connection failures, 429 and 5xx responses and other transport failures.
+    Network,
+    /// Errors reported by a node that may go away on their own.
+    Node,
+    NonRetryable,
+}
+
+impl Error {
+    pub fn retry_class(&self) -> RetryClass {
+        match self {
+            Error::Timeout(_) | Error::Transport(_) => RetryClass::Network,
+            Error::Reqwest(err) => match err.status() {
+                Some(status) if status.as_u16() == 429 || status.is_server_error() => {
+                    RetryClass::Network
+                }
+                Some(_) => RetryClass::NonRetryable,
+                None if err.is_timeout() || err.is_connect() || err.is_request() => {
+                    RetryClass::Network
+                }
+                None => RetryClass::NonRetryable,
+            },
+            Error::Rpc(err) => match err.kind() {
+                ErrorCode::InWarmup | ErrorCode::InternalError | ErrorCode::DatabaseError => {
+                    RetryClass::Node
+                }
+                _ => RetryClass::NonRetryable,
+            },
+        }
+    }
+
+    /// Returns whether the request failed before the node processed it: the connection could not
+    /// be established or was already closed, or the node turned it away with 429.
+    pub fn is_unsent(&self) -> bool {
+        match self {
+            Error::Reqwest(err) => {
+                err.is_connect() || err.status().is_some_and(|status| status.as_u16() == 429)
+            }
+            Error::Transport(err) => err.is::<Unsent>(),
+            _ => false,
+        }
+    }
+}
+
+/// A token bucket allowing bursts of up to one second worth of requests. The rate must have
+/// passed [`RetryPolicy::validate`].
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    rate: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(requests_per_second: f64) -> Self {
+        Self {
+            rate: requests_per_second,
+            state: Mutex::new((requests_per_second.max(1.0), Instant::now())),
+        }
+    }
+
+    /// Waits until a request may be sent.
+    pub(crate) async fn acquire(&self) {
+        let mut state = self.state.lock().await;
+        let (tokens, last) = &mut *state;
+
+        let now = Instant::now();
+        *tokens =
+            (*tokens + now.duration_since(*last).as_secs_f64() * self.rate).min(self.rate.max(1.0));
+        *last = now;
+
+        if *tokens < 1.0 {
+            let wait = Duration::from_secs_f64((1.0 - *tokens) / self.rate);
+            // Holding the lock while sleeping keeps waiting callers in order.
+            tokio::time::sleep(wait).await;
+            *tokens = 1.0;
+            *last = Instant::now();
+        }
+
+        *tokens -= 1.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonrpc::RpcError;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            jitter: false,
+            max_backoff: Duration::from_millis(500),
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(500));
+
+        let policy = RetryPolicy::default();
+        for retry in 0..5 {
+            let backoff = policy.backoff(retry);
+            let max = Duration::from_millis(100 * 2u64.pow(retry));
+            assert!(backoff >= max / 2 && backoff <= max);
+        }
+    }
+
+    #[test]
+    fn test_validate_rate() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let policy = RetryPolicy {
+                requests_per_second: Some(rate),
+                ..RetryPolicy::default()
+            };
+            assert!(matches!(
+                policy.validate(),
+                Err(PolicyError::InvalidRate(_))
+            ));
+        }
+
+        let policy = RetryPolicy {
+            requests_per_second: Some(0.5),
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_backoff_multiplier() {
+        for multiplier in [0.5, -2.0, f64::NAN] {
+            let policy = RetryPolicy {
+                backoff_multiplier: multiplier,
+                ..RetryPolicy::default()
+            };
+            assert!(matches!(
+                policy.validate(),
+                Err(PolicyError::InvalidBackoffMultiplier(_))
+            ));
+        }
+
+        let policy = RetryPolicy {
+            backoff_multiplier: 1.0,
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_client_rejects_invalid_policy() {
+        let result = crate::jsonrpc::RpcClient::with_policy(
+            "http://127.0.0.1".into(),
+            RetryPolicy {
+                requests_per_second: Some(0.0),
+                ..RetryPolicy::default()
+            },
+        );
+        assert_eq!(result.err(), Some(PolicyError::InvalidRate(0.0)));
+    }
+
+    #[test]
+    fn test_retry_class() {
+        let rpc_error = |code| {
+            Error::Rpc(RpcError {
+                code,
+                message: String::new(),
+                data: None,
+            })
+        };
+
+        assert_eq!(
+            Error::Timeout(Duration::from_secs(1)).retry_class(),
+            RetryClass::Network
+        );
+        assert_eq!(rpc_error(-28).retry_class(), RetryClass::Node);
+        assert_eq!(rpc_error(-5).retry_class(), RetryClass::NonRetryable);
+
+        assert!(!RetryPolicy {
+            retry_node_errors: false,
+            ..RetryPolicy::default()
+        }
+        .should_retry(&rpc_error(-28)));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter() {
+        let limiter = RateLimiter::new(20.0);
+        let start = tokio::time::Instant::now();
+
+        for _ in 0..24 {
+            limiter.acquire().await;
+        }
+
+        // Twenty requests are allowed straight away, the other four are spread over 200ms.
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(190), "{:?}", elapsed);
+    }
+}
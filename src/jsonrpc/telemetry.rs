@@ -0,0 +1,125 @@
+//! Spans and metrics for requests.
+//!
+//! Every request runs in a `rpc_call` span at debug level carrying the method, request ID,
+//! endpoint, duration and outcome. Params and results are never recorded, as they hold public
+//! keys, signatures and whatever else callers put in them.
+//!
+//! With the `metrics` feature, the following are reported through the [`metrics`] facade:
+//!
+//! - `zilliqa_rpc_requests_total`, a counter labelled with `method` and `outcome`
+//! - `zilliqa_rpc_request_duration_seconds`, a histogram labelled with `method`
+//! - `zilliqa_rpc_retries_total`, a counter labelled with `outcome`
+
+use std::time::Duration;
+
+use tracing::Span;
+
+use super::{Error, Result};
+
+/// Classifies a result into one of `ok`, `rpc_error`, `timeout` or `transport_error`.
+pub(crate) fn outcome<T>(result: &Result<T>) -> &'static str {
+    match result {
+        Ok(_) => "ok",
+        Err(err) => error_outcome(err),
+    }
+}
+
+fn error_outcome(err: &Error) -> &'static str {
+    match err {
+        Error::Rpc(_) => "rpc_error",
+        Error::Timeout(_) => "timeout",
+        Error::Reqwest(_) | Error::Transport(_) => "transport_error",
+    }
+}
+
+pub(crate) fn finish<T>(span: &Span, method: &str, elapsed: Duration, result: &Result<T>) {
+    let outcome = outcome(result);
+
+    span.record("duration_ms", elapsed.as_millis() as u64);
+    span.record("outcome", outcome);
+    if let Err(Error::Rpc(err)) = result {
+        span.record("error_code", err.code);
+    }
+    tracing::debug!(parent: span, outcome, duration_ms = elapsed.as_millis() as u64, "request finished");
+
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(
+            "zilliqa_rpc_requests_total",
+            "method" => method.to_string(),
+            "outcome" => outcome,
+        )
+        .increment(1);
+        metrics::histogram!(
+            "zilliqa_rpc_request_duration_seconds",
+            "method" => method.to_string(),
+        )
+        .record(elapsed.as_secs_f64());
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = method;
+}
+
+pub(crate) fn retry(err: &Error, retry: u32, backoff: Duration) {
+    let outcome = error_outcome(err);
+    tracing::debug!(
+        outcome,
+        retry,
+        backoff_ms = backoff.as_millis() as u64,
+        "retrying request"
+    );
+
+    #[cfg(feature = "metrics")]
+    metrics::counter!("zilliqa_rpc_retries_total", "outcome" => outcome).increment(1);
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// A subscriber keeping every field recorded by this crate as `name=value`.
+    #[derive(Clone, Default)]
+    pub(crate) struct Capture(pub Arc<Mutex<Vec<String>>>);
+
+    impl Visit for Capture {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{}={:?}", field.name(), value));
+        }
+    }
+
+    impl Subscriber for Capture {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.target().starts_with("zilliqa_sdk")
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("span={}", span.metadata().name()));
+            span.record(&mut self.clone());
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, values: &Record<'_>) {
+            values.record(&mut self.clone());
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut self.clone());
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use super::Unsent;
+use crate::jsonrpc::{Error, Result};
+
+/// Hands responses arriving on a shared connection to the callers waiting for them, by request
+/// ID. A batch is answered as a whole and is found by the ID of any of its requests.
+#[derive(Default)]
+pub(super) struct Router {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    closed: bool,
+    next_token: u64,
+    waiters: HashMap<u64, Waiter>,
+    routes: HashMap<String, u64>,
+}
+
+struct Waiter {
+    ids: Vec<String>,
+    sender: oneshot::Sender<Value>,
+}
+
+impl State {
+    fn remove(&mut self, token: u64) -> Option<Waiter> {
+        let waiter = self.waiters.remove(&token)?;
+        for id in &waiter.ids {
+            self.routes.remove(id);
+        }
+        Some(waiter)
+    }
+}
+
+/// A request waiting for its response. Dropping it, e.g. when the caller timed out, forgets the
+/// request, so a late response is discarded instead of being handed to someone else.
+pub(super) struct Pending<'a> {
+    router: &'a Router,
+    token: u64,
+    receiver: oneshot::Receiver<Value>,
+}
+
+impl Router {
+    /// Registers `request` before it is sent.
+    pub(super) fn register(&self, request: &Value) -> Result<Pending<'_>> {
+        let ids = match request {
+            Value::Array(requests) => requests.iter().map(request_id).collect::<Result<_>>()?,
+            request => vec![request_id(request)?],
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return Err(Unsent::error("connection closed"));
+        }
+        if let Some(id) = ids.iter().find(|id| state.routes.contains_key(*id)) {
+            return Err(Unsent::error(format!(
+                "a request with id {} is already in flight",
+                id
+            )));
+        }
+
+        let token = state.next_token;
+        state.next_token += 1;
+        for id in &ids {
+            state.routes.insert(id.clone(), token);
+        }
+        let (sender, receiver) = oneshot::channel();
+        state.waiters.insert(token, Waiter { ids, sender });
+
+        Ok(Pending {
+            router: self,
+            token,
+            receiver,
+        })
+    }
+
+    /// Passes a response read from the connection to its caller. Responses nobody waits for are
+    /// dropped.
+    pub(super) fn dispatch(&self, response: Value) {
+        let id = match &response {
+            Value::Array(responses) => responses.iter().find(|r| !r["id"].is_null()),
+            response => Some(response),
+        }
+        .map(|response| response["id"].to_string());
+
+        let mut state = self.state.lock().unwrap();
+        let waiter = id
+            .as_ref()
+            .and_then(|id| state.routes.get(id).copied())
+            .and_then(|token| state.remove(token));
+
+        match waiter {
+            Some(waiter) => {
+                let _ = waiter.sender.send(response);
+            }
+            None => tracing::debug!(id = id.as_deref(), "dropping unexpected response"),
+        }
+    }
+
+    /// Fails every pending and future request, once the connection is gone.
+    pub(super) fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        state.waiters.clear();
+        state.routes.clear();
+    }
+}
+
+impl Pending<'_> {
+    pub(super) async fn response(mut self) -> Result<Value> {
+        (&mut self.receiver)
+            .await
+            .map_err(|_| Error::Transport("connection closed".into()))
+    }
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        self.router.state.lock().unwrap().remove(self.token);
+    }
+}
+
+fn request_id(request: &Value) -> Result<String> {
+    match &request["id"] {
+        Value::Null => Err(Unsent::error("request without an id")),
+        id => Ok(id.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_route_by_id() {
+        let router = Router::default();
+        let first = router.register(&json!({"id": 1})).unwrap();
+        let batch = router.register(&json!([{"id": 2}, {"id": 3}])).unwrap();
+        assert!(router.register(&json!({"id": 3})).is_err());
+
+        router.dispatch(json!([{"id": 3, "result": "c"}, {"id": 2, "result": "b"}]));
+        router.dispatch(json!({"id": 1, "result": "a"}));
+
+        assert_eq!(first.response().await.unwrap()["result"], "a");
+        assert_eq!(batch.response().await.unwrap()[0]["result"], "c");
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_request() {
+        let router = Router::default();
+        drop(router.register(&json!({"id": 1})).unwrap());
+
+        // The late response to the abandoned request is not handed to the next one.
+        let next = router.register(&json!({"id": 2})).unwrap();
+        router.dispatch(json!({"id": 1, "result": "late"}));
+        router.dispatch(json!({"id": 2, "result": "mine"}));
+        assert_eq!(next.response().await.unwrap()["result"], "mine");
+
+        // Requests already sent may have been processed, later ones were not.
+        let pending = router.register(&json!({"id": 3})).unwrap();
+        router.close();
+        assert!(!pending.response().await.unwrap_err().is_unsent());
+        assert!(router
+            .register(&json!({"id": 4}))
+            .err()
+            .unwrap()
+            .is_unsent());
+    }
+}
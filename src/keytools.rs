@@ -1,3 +1,5 @@
+mod encoding;
 mod secp256k1;
 
+pub use self::encoding::*;
 pub use self::secp256k1::*;
@@ -2,8 +2,17 @@ pub mod account;
 pub mod bech32;
 pub mod core;
 pub mod crypto;
+#[cfg(any(test, feature = "devnet"))]
+pub mod devnet;
 pub mod jsonrpc;
 pub mod keytools;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 pub mod provider;
+pub mod siwz;
+#[cfg(any(test, feature = "ws"))]
+pub mod subscription;
+#[cfg(test)]
+mod test_support;
 pub mod transaction;
 pub mod util;
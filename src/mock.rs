@@ -0,0 +1,336 @@
+//! A scriptable Zilliqa node for tests, enabled with the `mock` feature.
+//!
+//! The server runs on its own thread, so it can be used from async tests as well as with the
+//! blocking clients:
+//!
+//! ```
+//! use serde_json::json;
+//! use zilliqa_sdk::mock::MockServer;
+//! use zilliqa_sdk::provider::blocking::Provider;
+//!
+//! let server = MockServer::start();
+//! server.mock("GetMinimumGasPrice").returns(json!("2000000000"));
+//!
+//! let provider = Provider::new(server.url());
+//! assert_eq!(provider.get_minimum_gas_price().unwrap(), "2000000000");
+//! assert_eq!(server.calls_to("GetMinimumGasPrice").len(), 1);
+//! ```
+
+use std::convert::Infallible;
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+
+pub mod fixtures;
+
+/// A request received by a [`MockServer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub id: Value,
+    pub method: String,
+    pub params: Value,
+}
+
+#[derive(Debug, Clone)]
+enum Reply {
+    Result(Value),
+    Error {
+        code: i64,
+        message: String,
+        data: Option<Value>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Mock {
+    method: String,
+    params: Option<Value>,
+    reply: Reply,
+}
+
+#[derive(Default)]
+struct State {
+    mocks: Vec<Mock>,
+    calls: Vec<Call>,
+}
+
+pub struct MockServer {
+    url: String,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Starts a server on a free localhost port. It stops when dropped.
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        listener.set_nonblocking(true).unwrap();
+
+        let state = Arc::new(Mutex::new(State::default()));
+        let (shutdown, stopped) = oneshot::channel::<()>();
+
+        let server_state = state.clone();
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("build runtime");
+
+            runtime.block_on(async move {
+                let make_service = make_service_fn(move |_| {
+                    let state = server_state.clone();
+                    async move {
+                        Ok::<_, Infallible>(service_fn(move |request| {
+                            handle(state.clone(), request)
+                        }))
+                    }
+                });
+
+                let _ = Server::from_tcp(listener)
+                    .expect("start mock server")
+                    .serve(make_service)
+                    .with_graceful_shutdown(async {
+                        let _ = stopped.await;
+                    })
+                    .await;
+            });
+        });
+
+        Self {
+            url,
+            state,
+            shutdown: Some(shutdown),
+        }
+    }
+
+    /// Registers the responses in [`fixtures`] for every provider method.
+    pub fn with_fixtures(self) -> Self {
+        for (method, result) in fixtures::all() {
+            self.mock(method).returns(result);
+        }
+
+        self
+    }
+
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Starts registering a response for `method`. Later registrations take precedence over
+    /// earlier ones, and methods without any registration answer with "method not found".
+    pub fn mock(&self, method: &str) -> MockBuilder<'_> {
+        MockBuilder {
+            server: self,
+            method: method.to_string(),
+            params: None,
+        }
+    }
+
+    /// Returns every request received so far, batches flattened, in order.
+    pub fn calls(&self) -> Vec<Call> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    pub fn calls_to(&self, method: &str) -> Vec<Call> {
+        self.calls()
+            .into_iter()
+            .filter(|call| call.method == method)
+            .collect()
+    }
+
+    /// Forgets all registered responses and recorded calls.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.mocks.clear();
+        state.calls.clear();
+    }
+
+    fn register(&self, mock: Mock) {
+        self.state.lock().unwrap().mocks.push(mock);
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+pub struct MockBuilder<'a> {
+    server: &'a MockServer,
+    method: String,
+    params: Option<Value>,
+}
+
+impl MockBuilder<'_> {
+    /// Only answers requests whose params equal `params`.
+    pub fn with_params(mut self, params: Value) -> Self {
+        self.params = Some(params);
+        self
+    }
+
+    pub fn returns(self, result: Value) {
+        self.reply(Reply::Result(result));
+    }
+
+    pub fn returns_error(self, code: i64, message: &str) {
+        self.reply(Reply::Error {
+            code,
+            message: message.to_string(),
+            data: None,
+        });
+    }
+
+    pub fn returns_error_with_data(self, code: i64, message: &str, data: Value) {
+        self.reply(Reply::Error {
+            code,
+            message: message.to_string(),
+            data: Some(data),
+        });
+    }
+
+    fn reply(self, reply: Reply) {
+        self.server.register(Mock {
+            method: self.method,
+            params: self.params,
+            reply,
+        });
+    }
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let body = hyper::body::to_bytes(request.into_body())
+        .await
+        .unwrap_or_default();
+
+    let response = match serde_json::from_slice::<Value>(&body) {
+        Ok(Value::Array(requests)) => Value::Array(
+            requests
+                .iter()
+                .map(|request| answer(&state, request))
+                .collect(),
+        ),
+        Ok(request) => answer(&state, &request),
+        Err(_) => error_response(Value::Null, -32700, "Parse error", None),
+    };
+
+    Ok(Response::builder()
+        .header("content-type", "application/json")
+        .body(Body::from(response.to_string()))
+        .unwrap())
+}
+
+fn answer(state: &Mutex<State>, request: &Value) -> Value {
+    let call = Call {
+        id: request["id"].clone(),
+        method: request["method"].as_str().unwrap_or_default().to_string(),
+        params: request["params"].clone(),
+    };
+
+    let mut state = state.lock().unwrap();
+    let reply = state
+        .mocks
+        .iter()
+        .rev()
+        .find(|mock| {
+            mock.method == call.method && mock.params.iter().all(|params| *params == call.params)
+        })
+        .map(|mock| mock.reply.clone());
+    state.calls.push(call.clone());
+
+    match reply {
+        Some(Reply::Result(result)) => json!({"id": call.id, "jsonrpc": "2.0", "result": result}),
+        Some(Reply::Error {
+            code,
+            message,
+            data,
+        }) => error_response(call.id, code, &message, data),
+        None => error_response(
+            call.id,
+            -32601,
+            "METHOD_NOT_FOUND: The method being requested is not available on this server",
+            None,
+        ),
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str, data: Option<Value>) -> Value {
+    json!({
+        "id": id,
+        "jsonrpc": "2.0",
+        "error": {"code": code, "message": message, "data": data},
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonrpc::{ErrorCode, RpcClient};
+
+    #[tokio::test]
+    async fn test_mock_server() {
+        let server = MockServer::start();
+        server
+            .mock("GetBalance")
+            .returns_error(-5, "Account is not created");
+        server
+            .mock("GetBalance")
+            .with_params(json!(["9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a"]))
+            .returns(json!({"balance": "100", "nonce": 1}));
+
+        let client = RpcClient::new(server.url());
+        let result = client
+            .call(
+                "GetBalance",
+                json!(["9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a"]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result, json!({"balance": "100", "nonce": 1}));
+
+        match client
+            .call(
+                "GetBalance",
+                json!(["0000000000000000000000000000000000000000"]),
+            )
+            .await
+        {
+            Err(crate::jsonrpc::Error::Rpc(err)) => {
+                assert_eq!(err.kind(), ErrorCode::InvalidAddressOrKey)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let results = client
+            .batch(vec![
+                ("GetNetworkId", Value::Null),
+                ("GetBalance", json!(["x"])),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(
+            results[0].as_ref().unwrap_err().kind(),
+            ErrorCode::MethodNotFound
+        );
+        assert!(results[1].is_err());
+
+        let calls = server.calls();
+        assert_eq!(calls.len(), 4);
+        assert_eq!(calls[2].method, "GetNetworkId");
+        assert_eq!(server.calls_to("GetBalance").len(), 3);
+
+        server.reset();
+        assert!(server.calls().is_empty());
+    }
+}
@@ -0,0 +1,260 @@
+//! Canned results for each [`Provider`](crate::provider::Provider) method, shaped like the
+//! responses of the public Zilliqa API.
+
+use serde_json::{json, Value};
+
+pub const ADDRESS: &str = "9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a";
+pub const CONTRACT_ADDRESS: &str = "9611c53be6d1b32058b2747bdececed7e1216793";
+pub const TRANSACTION_HASH: &str =
+    "f79b9a88bbe15a0af47880b4fa8dc0d15e9d5a05c4e89c59b1fc2abc9785fcf8";
+pub const TX_BLOCK_NUMBER: &str = "1442201";
+pub const DS_BLOCK_NUMBER: &str = "14423";
+
+/// Returns every fixture along with the method it answers.
+pub fn all() -> Vec<(&'static str, Value)> {
+    vec![
+        ("GetLatestTxBlock", tx_block()),
+        ("GetTxBlock", tx_block()),
+        ("TxBlockListing", tx_block_listing()),
+        ("GetLatestDsBlock", ds_block()),
+        ("GetDsBlock", ds_block()),
+        ("DSBlockListing", ds_block_listing()),
+        ("GetMinerInfo", miner_info()),
+        ("GetTransaction", transaction()),
+        ("GetTransactionsForTxBlock", transactions_for_tx_block()),
+        (
+            "GetTransactionsForTxBlockEx",
+            transactions_for_tx_block_ex(),
+        ),
+        ("GetTxBodiesForTxBlock", json!([transaction()])),
+        ("GetTxBodiesForTxBlockEx", tx_bodies_for_tx_block_ex()),
+        ("GetMinimumGasPrice", minimum_gas_price()),
+        ("GetNetworkId", network_id()),
+        ("GetBlockchainInfo", blockchain_info()),
+        ("GetShardingStructure", sharding_structure()),
+        ("GetNumTxBlocks", json!("1442202")),
+        ("GetNumDSBlocks", json!("14424")),
+        ("GetNumTransactions", json!("4350627")),
+        ("GetTransactionRate", json!(0.1218620867)),
+        ("GetTxBlockRate", json!(0.0142235)),
+        ("GetDSBlockRate", json!(0.0001422)),
+        ("GetTotalCoinSupply", json!("13452081092.277")),
+        ("GetPrevDifficulty", json!(91)),
+        ("GetPrevDSDifficulty", json!(149)),
+        ("GetSmartContractInit", smart_contract_init()),
+        ("GetSmartContractCode", smart_contract_code()),
+        ("GetSmartContractSubState", smart_contract_substate()),
+        (
+            "GetContractAddressFromTransactionID",
+            contract_address_from_transaction_id(),
+        ),
+        ("GetBalance", balance()),
+        ("CreateTransaction", create_transaction()),
+    ]
+}
+
+pub fn tx_block() -> Value {
+    json!({
+        "body": {
+            "BlockHash": "2d4e5c8d9e6c8d2bd1e61c4a8f5b3a7e3d1b0c8f77fb5ea5a4c8f26e8d4b8a11",
+            "HeaderSign": "9d27c4e3d1b5f1f0a1c3ea0bc33c6e2f0a0a7c9d7b2f3e1c5a8d8e7e6b2f1c0d9e8f7a6b5c4d3e2f1a0b9c8d7e6f5a4b3c2d1e0f9a8b7c6d5e4f3a2b1c0d9e8f",
+            "MicroBlockInfos": [
+                {
+                    "MicroBlockHash": "e4d6f1b0c3a2d5e8f7a6b9c0d1e2f3a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0",
+                    "MicroBlockShardId": 0,
+                    "MicroBlockTxnRootHash": "0b6e3c2d1a0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a2f1e0d9c8b7a6f5e4d"
+                }
+            ]
+        },
+        "header": {
+            "BlockNum": TX_BLOCK_NUMBER,
+            "DSBlockNum": "14423",
+            "GasLimit": "30000000",
+            "GasUsed": "250",
+            "MbInfoHash": "6a1fb7a6c1b8d9c1e7f6c4d1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1",
+            "MinerPubKey": "0x02C1A1D4A0B9E8E7B8C5D2F1E0A9B8C7D6E5F4A3B2C1D0E9F8A7B6C5D4E3F2A1B0",
+            "NumMicroBlocks": 1,
+            "NumPages": 1,
+            "NumTxns": 2,
+            "PrevBlockHash": "8b5c1e4d7a0f3e6d9c2b5a8f1e4d7c0b3a6f9e2d5c8b1a4f7e0d3c6b9a2f5e8d",
+            "Rewards": "2000000000000",
+            "StateDeltaHash": "3c5b4a1f2e0d9c8b7a6f5e4d3c2b1a0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b",
+            "StateRootHash": "5f0e1d2c3b4a59687f6e5d4c3b2a19087f6e5d4c3b2a19087f6e5d4c3b2a1908",
+            "Timestamp": "1614050293588925",
+            "TxnFees": "4000000000",
+            "Version": 1
+        }
+    })
+}
+
+pub fn tx_block_listing() -> Value {
+    json!({
+        "data": [
+            {"BlockNum": 1442201, "Hash": "2d4e5c8d9e6c8d2bd1e61c4a8f5b3a7e3d1b0c8f77fb5ea5a4c8f26e8d4b8a11"},
+            {"BlockNum": 1442200, "Hash": "8b5c1e4d7a0f3e6d9c2b5a8f1e4d7c0b3a6f9e2d5c8b1a4f7e0d3c6b9a2f5e8d"}
+        ],
+        "maxPages": 1
+    })
+}
+
+pub fn ds_block() -> Value {
+    json!({
+        "header": {
+            "BlockNum": DS_BLOCK_NUMBER,
+            "Difficulty": 91,
+            "DifficultyDS": 149,
+            "GasPrice": "2000000000",
+            "LeaderPubKey": "0x0245C0D2D5DB5B8F7C8C0B4A5E8F1D0C3B6A9E2D5C8B1A4F7E0D3C6B9A2F5E8D7C",
+            "PoWWinners": [
+                "0x02A0B1C2D3E4F5A6B7C8D9E0F1A2B3C4D5E6F7A8B9C0D1E2F3A4B5C6D7E8F9A0B1",
+                "0x03F9E8D7C6B5A4F3E2D1C0B9A8F7E6D5C4B3A2F1E0D9C8B7A6F5E4D3C2B1A0F9E8"
+            ],
+            "PrevHash": "4d3c2b1a0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a2f1e0d9c8b7a6f5e4d3c",
+            "Timestamp": "1614049093588925"
+        },
+        "signature": "7e6d5c4b3a2f1e0d9c8b7a6f5e4d3c2b1a0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a2f1e0d9c8b7a6f5e4d3c2b1a0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d"
+    })
+}
+
+pub fn ds_block_listing() -> Value {
+    json!({
+        "data": [
+            {"BlockNum": 14423, "Hash": "2b1a0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a2f1e0d9c8b7a6f5e4d3c2b1a"},
+            {"BlockNum": 14422, "Hash": "4d3c2b1a0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a2f1e0d9c8b7a6f5e4d3c"}
+        ],
+        "maxPages": 1443
+    })
+}
+
+pub fn miner_info() -> Value {
+    json!({
+        "dscommittee": [
+            "0x0245C0D2D5DB5B8F7C8C0B4A5E8F1D0C3B6A9E2D5C8B1A4F7E0D3C6B9A2F5E8D7C",
+            "0x02A0B1C2D3E4F5A6B7C8D9E0F1A2B3C4D5E6F7A8B9C0D1E2F3A4B5C6D7E8F9A0B1"
+        ],
+        "shards": [
+            {
+                "nodes": ["0x03F9E8D7C6B5A4F3E2D1C0B9A8F7E6D5C4B3A2F1E0D9C8B7A6F5E4D3C2B1A0F9E8"],
+                "size": 1
+            }
+        ]
+    })
+}
+
+pub fn transaction() -> Value {
+    json!({
+        "ID": TRANSACTION_HASH,
+        "amount": "10000000",
+        "data": "",
+        "gasLimit": "50",
+        "gasPrice": "2000000000",
+        "nonce": "1",
+        "priority": false,
+        "receipt": {
+            "cumulative_gas": "1",
+            "epoch_num": TX_BLOCK_NUMBER,
+            "success": true
+        },
+        "senderPubKey": "0x0246E7178DC8253201101E18FD6F6EB9972451D121FC57AA2A06DD5C111E58DC6A",
+        "signature": "0x81A7E6F4C5B1D0A2E3F4A5B6C7D8E9F0A1B2C3D4E5F6A7B8C9D0E1F2A3B4C5D6E7F8A9B0C1D2E3F4A5B6C7D8E9F0A1B2C3D4E5F6A7B8C9D0E1F2A3B4C5D6E7F8",
+        "toAddr": "4baf5fada8e5db92c3d3242618c5b47133ae003c",
+        "version": "21823489"
+    })
+}
+
+pub fn transactions_for_tx_block() -> Value {
+    json!([
+        [
+            TRANSACTION_HASH,
+            "5283d3a37d90b960ff2e7c6b2a6e8b0f5e62ed74f63b268b1b9485aa08026551"
+        ],
+        []
+    ])
+}
+
+pub fn transactions_for_tx_block_ex() -> Value {
+    json!({
+        "CurrPage": 0,
+        "NumPages": 1,
+        "Transactions": transactions_for_tx_block()
+    })
+}
+
+pub fn tx_bodies_for_tx_block_ex() -> Value {
+    json!({
+        "CurrPage": 0,
+        "NumPages": 1,
+        "Transactions": [transaction()]
+    })
+}
+
+pub fn minimum_gas_price() -> Value {
+    json!("2000000000")
+}
+
+pub fn network_id() -> Value {
+    json!("1")
+}
+
+pub fn blockchain_info() -> Value {
+    json!({
+        "CurrentDSEpoch": "14424",
+        "CurrentMiniEpoch": "1442202",
+        "DSBlockRate": 0.0001422,
+        "NumDSBlocks": "14424",
+        "NumPeers": 2400,
+        "NumTransactions": "4350627",
+        "NumTxBlocks": "1442202",
+        "NumTxnsDSEpoch": "748",
+        "NumTxnsTxEpoch": "2",
+        "ShardingStructure": sharding_structure(),
+        "TransactionRate": 0.1218620867,
+        "TxBlockRate": 0.0142235
+    })
+}
+
+pub fn sharding_structure() -> Value {
+    json!({"NumPeers": [600, 600, 600]})
+}
+
+pub fn smart_contract_init() -> Value {
+    json!([
+        {"type": "Uint32", "value": "0", "vname": "_scilla_version"},
+        {"type": "ByStr20", "value": format!("0x{}", ADDRESS), "vname": "initial_admin"},
+        {"type": "ByStr20", "value": format!("0x{}", CONTRACT_ADDRESS), "vname": "_this_address"},
+        {"type": "BNum", "value": "1442180", "vname": "_creation_block"}
+    ])
+}
+
+pub fn smart_contract_code() -> Value {
+    json!({
+        "code": "scilla_version 0\n\ncontract Admins(initial_admin: ByStr20)\n\nfield admins: Map ByStr20 Bool = Emp ByStr20 Bool\n"
+    })
+}
+
+pub fn smart_contract_substate() -> Value {
+    json!({
+        "admins": {
+            format!("0x{}", ADDRESS): {
+                "argtypes": [],
+                "arguments": [],
+                "constructor": "True"
+            }
+        }
+    })
+}
+
+pub fn contract_address_from_transaction_id() -> Value {
+    json!(CONTRACT_ADDRESS)
+}
+
+pub fn balance() -> Value {
+    json!({"balance": "18446744073637511711", "nonce": 16})
+}
+
+pub fn create_transaction() -> Value {
+    json!({
+        "Info": "Non-contract txn, sent to shard",
+        "TranID": "2d1eea871d8845472e98dbe9b7a7d788fbcce226f52e4216612592167b89042c"
+    })
+}
@@ -1,7 +1,22 @@
-use crate::core::{BalanceAndNonce, ContractValue, Transaction, TxBlock};
-use crate::jsonrpc::{reqwest, RpcClient};
+use crate::core::{
+    BalanceAndNonce, BlockListing, BlockListingEntry, BlockchainInfo, ContractValue,
+    CreateTransactionResponse, DsBlock, MinerInfo, ShardingStructure, SmartContractCode,
+    Transaction, TransactionsPage, TxBlock,
+};
+use crate::jsonrpc::{self, reqwest, telemetry, PolicyError, RetryPolicy, RpcClient, RpcError};
+use crate::util::{normalize_address, to_check_sum_address};
+use futures::{stream, Future, Stream, TryStreamExt};
 use serde_json::{json, Value};
+use std::time::Duration;
 use thiserror::Error;
+use tracing::field::Empty;
+use tracing::Instrument;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod middleware;
+
+pub use self::middleware::Middleware;
 
 #[derive(Error, Debug)]
 pub enum ProviderErrorSource {
@@ -9,12 +24,35 @@ pub enum ProviderErrorSource {
     Reqwest(#[from] reqwest::Error),
     #[error(transparent)]
     SerdeJson(#[from] serde_json::error::Error),
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+    #[error("request timed out after {0:?}")]
+    Timeout(Duration),
+    #[error(transparent)]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+    #[error("invalid transaction: {0}")]
+    InvalidTransaction(String),
+    /// An error raised by a [`Middleware`] layer rather than by the node.
+    #[error(transparent)]
+    Middleware(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl From<jsonrpc::Error> for ProviderErrorSource {
+    fn from(err: jsonrpc::Error) -> Self {
+        match err {
+            jsonrpc::Error::Reqwest(err) => err.into(),
+            jsonrpc::Error::Rpc(err) => err.into(),
+            jsonrpc::Error::Timeout(timeout) => Self::Timeout(timeout),
+            jsonrpc::Error::Transport(err) => Self::Transport(err),
+        }
+    }
 }
 
 #[derive(Error, Debug)]
 #[error("{source}")]
 pub struct ProviderError {
     pub method: Option<String>,
+    pub id: Option<u64>,
     pub source: ProviderErrorSource,
 }
 
@@ -22,15 +60,41 @@ impl ProviderError {
     pub fn new_with_method(source: ProviderErrorSource, method: String) -> Self {
         Self {
             method: Some(method),
+            id: None,
             source,
         }
     }
+
+    pub fn new_with_request(source: ProviderErrorSource, method: String, id: u64) -> Self {
+        Self {
+            method: Some(method),
+            id: Some(id),
+            source,
+        }
+    }
+
+    pub fn middleware<E: Into<Box<dyn std::error::Error + Send + Sync>>>(err: E) -> Self {
+        Self {
+            method: None,
+            id: None,
+            source: ProviderErrorSource::Middleware(err.into()),
+        }
+    }
+
+    /// Returns the error reported by the node, if any.
+    pub fn rpc_error(&self) -> Option<&RpcError> {
+        match &self.source {
+            ProviderErrorSource::Rpc(err) => Some(err),
+            _ => None,
+        }
+    }
 }
 
 impl From<reqwest::Error> for ProviderError {
     fn from(err: reqwest::Error) -> Self {
         Self {
             method: None,
+            id: None,
             source: err.into(),
         }
     }
@@ -40,6 +104,7 @@ impl From<serde_json::error::Error> for ProviderError {
     fn from(err: serde_json::error::Error) -> Self {
         Self {
             method: None,
+            id: None,
             source: err.into(),
         }
     }
@@ -58,33 +123,211 @@ impl Provider {
         }
     }
 
-    pub fn get_latest_tx_block(&self) -> Result<TxBlock> {
-        self.call("GetLatestTxBlock", Value::Null)
+    /// Creates a provider whose requests all follow `policy`. Fails if `policy` does not pass
+    /// [`RetryPolicy::validate`].
+    pub fn with_policy(
+        host: String,
+        policy: RetryPolicy,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(Self::with_client(RpcClient::with_policy(host, policy)?))
+    }
+
+    /// Creates a provider on top of a preconfigured client, e.g. one made with
+    /// [`RpcClient::builder`] or using another [`Transport`](crate::jsonrpc::Transport).
+    pub fn with_client(rpc_client: RpcClient) -> Self {
+        Self { rpc_client }
+    }
+
+    pub async fn get_latest_tx_block(&self) -> Result<TxBlock> {
+        self.call("GetLatestTxBlock", Value::Null).await
     }
 
-    pub fn get_transaction(&self, transaction_hash: &str) -> Result<Transaction> {
+    pub async fn get_tx_block(&self, block_number: &str) -> Result<TxBlock> {
+        self.call("GetTxBlock", json!([block_number.to_string()]))
+            .await
+    }
+
+    /// Returns page `page` of the most recent TxBlocks, counting from 1.
+    pub async fn get_tx_block_listing(&self, page: u32) -> Result<BlockListing> {
+        self.call("TxBlockListing", json!([page])).await
+    }
+
+    /// See [`Middleware::iter_tx_block_listing`].
+    pub fn iter_tx_block_listing(&self) -> impl Stream<Item = Result<BlockListingEntry>> + '_ {
+        Middleware::iter_tx_block_listing(self)
+    }
+
+    pub async fn get_latest_ds_block(&self) -> Result<DsBlock> {
+        self.call("GetLatestDsBlock", Value::Null).await
+    }
+
+    pub async fn get_ds_block(&self, block_number: &str) -> Result<DsBlock> {
+        self.call("GetDsBlock", json!([block_number.to_string()]))
+            .await
+    }
+
+    /// Returns page `page` of the most recent DS blocks, counting from 1.
+    pub async fn get_ds_block_listing(&self, page: u32) -> Result<BlockListing> {
+        self.call("DSBlockListing", json!([page])).await
+    }
+
+    /// Returns the DS committee and shard members of the DS epoch `ds_block_number`.
+    pub async fn get_miner_info(&self, ds_block_number: &str) -> Result<MinerInfo> {
+        self.call("GetMinerInfo", json!([ds_block_number.to_string()]))
+            .await
+    }
+
+    pub async fn get_transaction(&self, transaction_hash: &str) -> Result<Transaction> {
         self.call("GetTransaction", json!([transaction_hash.to_string()]))
+            .await
     }
 
-    pub fn get_transactions_for_tx_block(&self, tx_block_number: &str) -> Result<Vec<Vec<String>>> {
+    pub async fn get_transactions_for_tx_block(
+        &self,
+        tx_block_number: &str,
+    ) -> Result<Vec<Vec<String>>> {
         self.call(
             "GetTransactionsForTxBlock",
             json!([tx_block_number.to_string()]),
         )
+        .await
     }
 
-    pub fn get_minimum_gas_price(&self) -> Result<String> {
-        self.call("GetMinimumGasPrice", Value::Null)
+    pub async fn get_transactions_for_tx_block_ex(
+        &self,
+        tx_block_number: &str,
+        page: u32,
+    ) -> Result<TransactionsPage<Vec<String>>> {
+        self.call(
+            "GetTransactionsForTxBlockEx",
+            json!([tx_block_number.to_string(), page.to_string()]),
+        )
+        .await
     }
 
-    pub fn get_smart_contract_init(&self, contract_address: &str) -> Result<Vec<ContractValue>> {
+    /// Returns the transactions of a TxBlock like
+    /// [`get_transactions_for_tx_block`](Self::get_transactions_for_tx_block), but in full rather
+    /// than by hash.
+    pub async fn get_tx_bodies_for_tx_block(
+        &self,
+        tx_block_number: &str,
+    ) -> Result<Vec<Transaction>> {
+        self.call(
+            "GetTxBodiesForTxBlock",
+            json!([tx_block_number.to_string()]),
+        )
+        .await
+    }
+
+    pub async fn get_tx_bodies_for_tx_block_ex(
+        &self,
+        tx_block_number: &str,
+        page: u32,
+    ) -> Result<TransactionsPage<Transaction>> {
+        self.call(
+            "GetTxBodiesForTxBlockEx",
+            json!([tx_block_number.to_string(), page.to_string()]),
+        )
+        .await
+    }
+
+    /// See [`Middleware::iter_transactions_for_tx_block`].
+    pub fn iter_transactions_for_tx_block<'a>(
+        &'a self,
+        tx_block_number: &'a str,
+    ) -> impl Stream<Item = Result<String>> + 'a {
+        Middleware::iter_transactions_for_tx_block(self, tx_block_number)
+    }
+
+    /// See [`Middleware::iter_tx_bodies_for_tx_block`].
+    pub fn iter_tx_bodies_for_tx_block<'a>(
+        &'a self,
+        tx_block_number: &'a str,
+    ) -> impl Stream<Item = Result<Transaction>> + 'a {
+        Middleware::iter_tx_bodies_for_tx_block(self, tx_block_number)
+    }
+
+    pub async fn get_minimum_gas_price(&self) -> Result<String> {
+        self.call("GetMinimumGasPrice", Value::Null).await
+    }
+
+    pub async fn get_network_id(&self) -> Result<String> {
+        self.call("GetNetworkId", Value::Null).await
+    }
+
+    pub async fn get_blockchain_info(&self) -> Result<BlockchainInfo> {
+        self.call("GetBlockchainInfo", Value::Null).await
+    }
+
+    pub async fn get_sharding_structure(&self) -> Result<ShardingStructure> {
+        self.call("GetShardingStructure", Value::Null).await
+    }
+
+    pub async fn get_num_tx_blocks(&self) -> Result<String> {
+        self.call("GetNumTxBlocks", Value::Null).await
+    }
+
+    pub async fn get_num_ds_blocks(&self) -> Result<String> {
+        self.call("GetNumDSBlocks", Value::Null).await
+    }
+
+    pub async fn get_num_transactions(&self) -> Result<String> {
+        self.call("GetNumTransactions", Value::Null).await
+    }
+
+    /// Returns the current number of transactions per second.
+    pub async fn get_transaction_rate(&self) -> Result<f64> {
+        self.call("GetTransactionRate", Value::Null).await
+    }
+
+    /// Returns the current number of TX blocks per second.
+    pub async fn get_tx_block_rate(&self) -> Result<f64> {
+        self.call("GetTxBlockRate", Value::Null).await
+    }
+
+    /// Returns the current number of DS blocks per second.
+    pub async fn get_ds_block_rate(&self) -> Result<f64> {
+        self.call("GetDSBlockRate", Value::Null).await
+    }
+
+    /// Returns the total supply of ZIL, as a decimal number of ZIL rather than Qa.
+    pub async fn get_total_coin_supply(&self) -> Result<String> {
+        self.call("GetTotalCoinSupply", Value::Null).await
+    }
+
+    /// Returns the minimum shard PoW difficulty of the previous block.
+    pub async fn get_prev_difficulty(&self) -> Result<u32> {
+        self.call("GetPrevDifficulty", Value::Null).await
+    }
+
+    /// Returns the minimum DS PoW difficulty of the previous block.
+    pub async fn get_prev_ds_difficulty(&self) -> Result<u32> {
+        self.call("GetPrevDSDifficulty", Value::Null).await
+    }
+
+    pub async fn get_smart_contract_init(
+        &self,
+        contract_address: &str,
+    ) -> Result<Vec<ContractValue>> {
         self.call(
             "GetSmartContractInit",
             json!([contract_address.to_string()]),
         )
+        .await
+    }
+
+    pub async fn get_smart_contract_code(
+        &self,
+        contract_address: &str,
+    ) -> Result<SmartContractCode> {
+        self.call(
+            "GetSmartContractCode",
+            json!([contract_address.to_string()]),
+        )
+        .await
     }
 
-    pub fn get_smart_contract_substate(
+    pub async fn get_smart_contract_substate(
         &self,
         contract_address: &str,
         variable_name: &str,
@@ -98,99 +341,599 @@ impl Provider {
                 indices,
             ]),
         )
+        .await
     }
 
-    pub fn get_contract_address_from_transaction_id(&self, transaction_id: &str) -> Result<String> {
+    pub async fn get_contract_address_from_transaction_id(
+        &self,
+        transaction_id: &str,
+    ) -> Result<String> {
         self.call(
             "GetContractAddressFromTransactionID",
             json!([transaction_id.to_string()]),
         )
+        .await
     }
 
-    pub fn get_balance(&self, user_address: &str) -> Result<BalanceAndNonce> {
+    pub async fn get_balance(&self, user_address: &str) -> Result<BalanceAndNonce> {
         self.call("GetBalance", json!([user_address.to_string()]))
+            .await
     }
 
-    fn call<T: serde::de::DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
-        let response = self
+    /// Sends a signed transaction to the network.
+    pub async fn create_transaction(&self, tx: &Transaction) -> Result<CreateTransactionResponse> {
+        let params = create_transaction_params(tx).map_err(|err| {
+            ProviderError::new_with_method(
+                ProviderErrorSource::InvalidTransaction(err),
+                "CreateTransaction".to_string(),
+            )
+        })?;
+
+        self.call("CreateTransaction", json!([params])).await
+    }
+
+    /// Fetches the balances of many addresses in a single request. Each address gets its own
+    /// result, so an address that does not exist does not fail the whole batch.
+    pub async fn batch_get_balance(
+        &self,
+        user_addresses: &[&str],
+    ) -> Result<Vec<Result<BalanceAndNonce>>> {
+        let params = user_addresses
+            .iter()
+            .map(|address| json!([address]))
+            .collect();
+        self.batch("GetBalance", params).await
+    }
+
+    pub async fn batch_get_transaction(
+        &self,
+        transaction_hashes: &[&str],
+    ) -> Result<Vec<Result<Transaction>>> {
+        let params = transaction_hashes
+            .iter()
+            .map(|hash| json!([hash]))
+            .collect();
+        self.batch("GetTransaction", params).await
+    }
+
+    async fn batch<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Vec<Value>,
+    ) -> Result<Vec<Result<T>>> {
+        let calls: Vec<_> = params
+            .into_iter()
+            .map(|params| (self.rpc_client.next_id(), method, params))
+            .collect();
+        let ids: Vec<u64> = calls.iter().map(|(id, _, _)| *id).collect();
+
+        let span =
+            tracing::debug_span!("provider_batch", method, size = ids.len(), outcome = Empty);
+        let results = self
             .rpc_client
-            .call(method, params)
+            .batch_with_ids(calls)
+            .instrument(span.clone())
+            .await;
+        span.record("outcome", telemetry::outcome(&results));
+
+        let results = results
             .map_err(|err| ProviderError::new_with_method(err.into(), method.to_string()))?;
 
-        serde_json::from_value(response.clone())
-            .map_err(|err| ProviderError::new_with_method(err.into(), method.to_string()))
+        Ok(ids
+            .into_iter()
+            .zip(results)
+            .map(|(id, result)| {
+                result
+                    .map_err(ProviderErrorSource::from)
+                    .and_then(|value| serde_json::from_value(value).map_err(Into::into))
+                    .map_err(|err| ProviderError::new_with_request(err, method.to_string(), id))
+            })
+            .collect())
     }
+
+    async fn call<T: serde::de::DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        // The request is traced by the client's `rpc_call` span.
+        let id = self.rpc_client.next_id();
+        let response = self
+            .rpc_client
+            .call_with_id(id, method, params)
+            .await
+            .map_err(|err| ProviderError::new_with_request(err.into(), method.to_string(), id))?;
+
+        serde_json::from_value(response).map_err(|err| {
+            tracing::debug!(method, id, %err, "invalid response");
+            ProviderError::new_with_request(err.into(), method.to_string(), id)
+        })
+    }
+}
+
+/// Streams the items of pages `first`, `first + 1`, ... until the end reported by `fetch`
+/// along with each page, which is exclusive.
+fn paginate<'a, T, F, Fut>(first: u32, fetch: F) -> impl Stream<Item = Result<T>> + 'a
+where
+    T: 'a,
+    F: Fn(u32) -> Fut + 'a,
+    Fut: Future<Output = Result<(Vec<T>, u32)>> + 'a,
+{
+    stream::try_unfold((first, u32::MAX), move |(page, end)| {
+        let fetched = fetch(page);
+        async move {
+            if page >= end {
+                return Result::Ok(None);
+            }
+            let (items, end) = fetched.await?;
+            Ok(Some((items, (page + 1, end))))
+        }
+    })
+    .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+    .try_flatten()
+}
+
+fn create_transaction_params(tx: &Transaction) -> std::result::Result<Value, String> {
+    let signature = tx
+        .signature
+        .as_deref()
+        .ok_or_else(|| String::from("transaction is not signed"))?;
+    let nonce: u64 = tx
+        .nonce
+        .as_deref()
+        .ok_or_else(|| String::from("transaction has no nonce"))?
+        .parse()
+        .map_err(|err| format!("invalid nonce: {}", err))?;
+    let version: u32 = tx
+        .version
+        .parse()
+        .map_err(|err| format!("invalid version: {}", err))?;
+    let to_addr = normalize_address(&tx.to_addr).map_err(|err| err.to_string())?;
+
+    Ok(json!({
+        "version": version,
+        "nonce": nonce,
+        "toAddr": to_check_sum_address(&to_addr).trim_start_matches("0x"),
+        "amount": tx.amount,
+        "pubKey": tx.sender_pub_key.trim_start_matches("0x"),
+        "gasPrice": tx.gas_price,
+        "gasLimit": tx.gas_limit,
+        "code": tx.code.as_deref().unwrap_or_default(),
+        "data": tx.data.as_deref().unwrap_or_default(),
+        "signature": signature.trim_start_matches("0x"),
+        "priority": tx.priority,
+    }))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::jsonrpc::ErrorCode;
+    use crate::mock::{fixtures, MockServer};
 
-    #[test]
-    fn test_get_latest_tx_block() {
-        let provider = Provider::new("https://dev-api.zilliqa.com".into());
-        let result = provider.get_latest_tx_block().unwrap();
-        println!("{:?}", result);
+    fn provider() -> (MockServer, Provider) {
+        let server = MockServer::start().with_fixtures();
+        let provider = Provider::new(server.url());
+        (server, provider)
     }
 
-    #[test]
-    fn test_get_transaction() {
-        let provider = Provider::new("https://api.zilliqa.com".into());
+    #[tokio::test]
+    async fn test_get_latest_tx_block() {
+        let (_server, provider) = provider();
+        let result = provider.get_latest_tx_block().await.unwrap();
+        assert_eq!(result.header.block_num, fixtures::TX_BLOCK_NUMBER);
+        assert_eq!(result.body.micro_block_infos.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_tx_block() {
+        let (server, provider) = provider();
         let result = provider
-            .get_transaction("f79b9a88bbe15a0af47880b4fa8dc0d15e9d5a05c4e89c59b1fc2abc9785fcf8")
+            .get_tx_block(fixtures::TX_BLOCK_NUMBER)
+            .await
             .unwrap();
-        println!("{:?}", result);
+        assert_eq!(result.header.num_txns, 2);
+        assert_eq!(
+            server.calls_to("GetTxBlock")[0].params,
+            json!([fixtures::TX_BLOCK_NUMBER])
+        );
     }
 
-    #[test]
-    fn test_get_transactions_for_tx_block() {
-        let provider = Provider::new("https://dev-api.zilliqa.com".into());
-        let result = provider.get_transactions_for_tx_block("1442201").unwrap();
-        println!("{:?}", result);
+    #[tokio::test]
+    async fn test_iter_tx_block_listing() {
+        let (server, provider) = provider();
+        let result = provider.get_tx_block_listing(1).await.unwrap();
+        assert_eq!(result.data[0].block_num, 1442201);
+
+        let mut listing = fixtures::tx_block_listing();
+        listing["maxPages"] = json!(2);
+        server
+            .mock("TxBlockListing")
+            .with_params(json!([1]))
+            .returns(listing);
+        server
+            .mock("TxBlockListing")
+            .with_params(json!([2]))
+            .returns(json!({
+                "data": [
+                    {"BlockNum": 1442200, "Hash": fixtures::TRANSACTION_HASH},
+                    {"BlockNum": 1442199, "Hash": fixtures::TRANSACTION_HASH}
+                ],
+                "maxPages": 2
+            }));
+
+        // A new block shifted the second page back by one.
+        let blocks: Vec<_> = provider
+            .iter_tx_block_listing()
+            .map_ok(|entry| entry.block_num)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(blocks, vec![1442201, 1442200, 1442199]);
+
+        // Every middleware can walk the pages.
+        let middleware = middleware::LoggingMiddleware::new(provider);
+        let blocks: Vec<_> = middleware
+            .iter_tx_block_listing()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(blocks.len(), 3);
     }
 
-    #[test]
-    fn test_get_minimum_gas_price() {
-        let provider = Provider::new("https://dev-api.zilliqa.com/".into());
-        let result = provider.get_minimum_gas_price().unwrap();
-        println!("{:?}", result);
+    #[tokio::test]
+    async fn test_get_transactions_for_tx_block_ex() {
+        let (server, provider) = provider();
+        let result = provider
+            .get_transactions_for_tx_block_ex(fixtures::TX_BLOCK_NUMBER, 0)
+            .await
+            .unwrap();
+        assert_eq!(result.num_pages, 1);
+        assert_eq!(result.transactions[0][0], fixtures::TRANSACTION_HASH);
+        assert_eq!(
+            server.calls_to("GetTransactionsForTxBlockEx")[0].params,
+            json!([fixtures::TX_BLOCK_NUMBER, "0"])
+        );
+
+        let result = provider
+            .get_tx_bodies_for_tx_block(fixtures::TX_BLOCK_NUMBER)
+            .await
+            .unwrap();
+        assert_eq!(result[0].id.as_deref(), Some(fixtures::TRANSACTION_HASH));
+
+        let result = provider
+            .get_tx_bodies_for_tx_block_ex(fixtures::TX_BLOCK_NUMBER, 0)
+            .await
+            .unwrap();
+        assert_eq!(result.curr_page, 0);
+        assert_eq!(result.transactions.len(), 1);
     }
 
-    #[test]
-    fn test_get_smart_contract_init() {
-        let provider = Provider::new("https://api.zilliqa.com".into());
+    #[tokio::test]
+    async fn test_iter_transactions_for_tx_block() {
+        let (server, provider) = provider();
+        let mut page = fixtures::transactions_for_tx_block_ex();
+        page["NumPages"] = json!(2);
+        server
+            .mock("GetTransactionsForTxBlockEx")
+            .with_params(json!([fixtures::TX_BLOCK_NUMBER, "0"]))
+            .returns(page);
+        server
+            .mock("GetTransactionsForTxBlockEx")
+            .with_params(json!([fixtures::TX_BLOCK_NUMBER, "1"]))
+            .returns(json!({"CurrPage": 1, "NumPages": 2, "Transactions": [[], ["0xabc"]]}));
+
+        let hashes: Vec<String> = provider
+            .iter_transactions_for_tx_block(fixtures::TX_BLOCK_NUMBER)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(hashes.len(), 3);
+        assert_eq!(hashes[0], fixtures::TRANSACTION_HASH);
+        assert_eq!(hashes[2], "0xabc");
+
+        let bodies: Vec<Transaction> = provider
+            .iter_tx_bodies_for_tx_block(fixtures::TX_BLOCK_NUMBER)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(bodies.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_iter_empty_tx_block() {
+        let (server, provider) = provider();
+        server
+            .mock("GetTransactionsForTxBlockEx")
+            .returns_error(-1, "TxBlock has no transactions");
+        server
+            .mock("GetTxBodiesForTxBlockEx")
+            .returns_error(-1, "TxBlock has no transactions");
+
+        let hashes: Vec<String> = provider
+            .iter_transactions_for_tx_block(fixtures::TX_BLOCK_NUMBER)
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(hashes.is_empty());
+        let bodies: Vec<Transaction> = provider
+            .iter_tx_bodies_for_tx_block(fixtures::TX_BLOCK_NUMBER)
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(bodies.is_empty());
+        assert_eq!(server.calls_to("GetTransactionsForTxBlockEx").len(), 1);
+        assert!(server.calls_to("GetTxBlock").is_empty());
+
+        // Other errors are still surfaced.
+        server
+            .mock("GetTransactionsForTxBlockEx")
+            .returns_error(-5, "Invalid block number");
+        assert!(provider
+            .iter_transactions_for_tx_block(fixtures::TX_BLOCK_NUMBER)
+            .try_collect::<Vec<_>>()
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_ds_block() {
+        let (server, provider) = provider();
         let result = provider
-            .get_smart_contract_init("9611c53BE6d1b32058b2747bdeCECed7e1216793")
+            .get_ds_block(fixtures::DS_BLOCK_NUMBER)
+            .await
             .unwrap();
-        println!("{:?}", result);
+        assert_eq!(result.header.difficulty, 91);
+        assert_eq!(result.header.difficulty_ds, 149);
+        assert_eq!(result.header.pow_winners.len(), 2);
+        assert_eq!(
+            server.calls_to("GetDsBlock")[0].params,
+            json!([fixtures::DS_BLOCK_NUMBER])
+        );
+
+        let result = provider.get_latest_ds_block().await.unwrap();
+        assert_eq!(result.header.block_num, fixtures::DS_BLOCK_NUMBER);
     }
 
-    #[test]
-    fn test_get_smart_contract_substate() {
-        let provider = Provider::new("https://api.zilliqa.com".into());
+    #[tokio::test]
+    async fn test_get_ds_block_listing() {
+        let (server, provider) = provider();
+        let result = provider.get_ds_block_listing(1).await.unwrap();
+        assert_eq!(result.max_pages, 1443);
+        assert_eq!(result.data[0].block_num, 14423);
+        assert_eq!(server.calls_to("DSBlockListing")[0].params, json!([1]));
+    }
+
+    #[tokio::test]
+    async fn test_get_miner_info() {
+        let (_server, provider) = provider();
         let result = provider
-            .get_smart_contract_substate("9611c53BE6d1b32058b2747bdeCECed7e1216793", "admins", &[])
+            .get_miner_info(fixtures::DS_BLOCK_NUMBER)
+            .await
             .unwrap();
-        println!("{:?}", result);
+        assert_eq!(result.ds_committee.len(), 2);
+        assert_eq!(result.shards[0].size, 1);
     }
 
-    #[test]
-    fn get_contract_address_from_transaction_id() {
-        let provider = Provider::new("https://dev-api.zilliqa.com".into());
+    #[tokio::test]
+    async fn test_get_transaction() {
+        let (server, provider) = provider();
+        let result = provider
+            .get_transaction(fixtures::TRANSACTION_HASH)
+            .await
+            .unwrap();
+        assert_eq!(result.id.as_deref(), Some(fixtures::TRANSACTION_HASH));
+        assert!(result.receipt.unwrap().success);
+        assert_eq!(
+            server.calls_to("GetTransaction")[0].params,
+            json!([fixtures::TRANSACTION_HASH])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_transactions_for_tx_block() {
+        let (_server, provider) = provider();
+        let result = provider
+            .get_transactions_for_tx_block(fixtures::TX_BLOCK_NUMBER)
+            .await
+            .unwrap();
+        assert_eq!(result[0][0], fixtures::TRANSACTION_HASH);
+        assert!(result[1].is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_minimum_gas_price() {
+        let (_server, provider) = provider();
+        let result = provider.get_minimum_gas_price().await.unwrap();
+        assert_eq!(result, "2000000000");
+    }
+
+    #[tokio::test]
+    async fn test_get_blockchain_info() {
+        let (_server, provider) = provider();
+        assert_eq!(provider.get_network_id().await.unwrap(), "1");
+
+        let result = provider.get_blockchain_info().await.unwrap();
+        assert_eq!(result.current_ds_epoch, "14424");
+        assert_eq!(result.num_txns_ds_epoch, "748");
+        assert_eq!(result.sharding_structure.num_peers, vec![600, 600, 600]);
+
+        let result = provider.get_sharding_structure().await.unwrap();
+        assert_eq!(result.num_peers.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_network_statistics() {
+        let (_server, provider) = provider();
+        assert_eq!(provider.get_num_tx_blocks().await.unwrap(), "1442202");
+        assert_eq!(provider.get_num_ds_blocks().await.unwrap(), "14424");
+        assert_eq!(provider.get_num_transactions().await.unwrap(), "4350627");
+        assert!(provider.get_transaction_rate().await.unwrap() > 0.0);
+        assert!(provider.get_tx_block_rate().await.unwrap() > 0.0);
+        assert!(provider.get_ds_block_rate().await.unwrap() > 0.0);
+        assert_eq!(
+            provider.get_total_coin_supply().await.unwrap(),
+            "13452081092.277"
+        );
+        assert_eq!(provider.get_prev_difficulty().await.unwrap(), 91);
+        assert_eq!(provider.get_prev_ds_difficulty().await.unwrap(), 149);
+    }
+
+    #[tokio::test]
+    async fn test_get_smart_contract_init() {
+        let (_server, provider) = provider();
+        let result = provider
+            .get_smart_contract_init(fixtures::CONTRACT_ADDRESS)
+            .await
+            .unwrap();
+        assert_eq!(result[0].vname, "_scilla_version");
+    }
+
+    #[tokio::test]
+    async fn test_get_smart_contract_code() {
+        let (_server, provider) = provider();
+        let result = provider
+            .get_smart_contract_code(fixtures::CONTRACT_ADDRESS)
+            .await
+            .unwrap();
+        assert!(result.code.starts_with("scilla_version 0"));
+    }
+
+    #[tokio::test]
+    async fn test_get_smart_contract_substate() {
+        let (server, provider) = provider();
+        let result = provider
+            .get_smart_contract_substate(fixtures::CONTRACT_ADDRESS, "admins", &[])
+            .await
+            .unwrap();
+        assert!(result["admins"].is_object());
+        assert_eq!(
+            server.calls_to("GetSmartContractSubState")[0].params,
+            json!([fixtures::CONTRACT_ADDRESS, "admins", []])
+        );
+    }
+
+    #[tokio::test]
+    async fn get_contract_address_from_transaction_id() {
+        let (_server, provider) = provider();
         let result = provider
             .get_contract_address_from_transaction_id(
                 "5283d3a37d90b960ff2e7c6b2a6e8b0f5e62ed74f63b268b1b9485aa08026551",
             )
+            .await
             .unwrap();
-        println!("{:?}", result);
+        assert_eq!(result, fixtures::CONTRACT_ADDRESS);
     }
 
-    #[test]
-    fn test_get_balance() {
-        let provider = Provider::new("https://dev-api.zilliqa.com".into());
+    #[tokio::test]
+    async fn test_get_balance() {
+        let (_server, provider) = provider();
+        let result = provider.get_balance(fixtures::ADDRESS).await.unwrap();
+        assert_eq!(result.nonce, 16);
+    }
+
+    #[tokio::test]
+    async fn test_get_balance_error() {
+        let (server, provider) = provider();
+        server
+            .mock("GetBalance")
+            .returns_error(-5, "Account is not created");
+
+        let err = provider.get_balance(fixtures::ADDRESS).await.unwrap_err();
+        assert_eq!(err.method.as_deref(), Some("GetBalance"));
+        assert_eq!(
+            err.rpc_error().unwrap().kind(),
+            ErrorCode::InvalidAddressOrKey
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_get_balance() {
+        let (server, provider) = provider();
+        server
+            .mock("GetBalance")
+            .with_params(json!(["0000000000000000000000000000000000000000"]))
+            .returns_error(-5, "Account is not created");
+
         let result = provider
-            .get_balance("9bfec715a6bd658fcb62b0f8cc9bfa2ade71434a")
+            .batch_get_balance(&[
+                fixtures::ADDRESS,
+                "0000000000000000000000000000000000000000",
+            ])
+            .await
             .unwrap();
-        println!("{:?}", result);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].as_ref().unwrap().nonce, 16);
+        assert!(result[1].as_ref().unwrap_err().rpc_error().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_create_transaction() {
+        let (server, provider) = provider();
+        let mut tx: Transaction = serde_json::from_value(fixtures::transaction()).unwrap();
+        tx.receipt = None;
+        crate::transaction::sign_transaction(
+            &mut tx,
+            &hex::decode("e19d05c5452598e24caad4a0d85a49146f7be089515c905ae6a19e8a578a6930")
+                .unwrap(),
+        )
+        .unwrap();
+
+        let result = provider.create_transaction(&tx).await.unwrap();
+        assert_eq!(result.info, "Non-contract txn, sent to shard");
+
+        let params = &server.calls_to("CreateTransaction")[0].params[0];
+        assert_eq!(params["nonce"], json!(1));
+        assert_eq!(params["toAddr"], json!(crate::test_support::RECIPIENT));
+        assert_eq!(params["signature"], json!(tx.signature.unwrap()));
+    }
+
+    #[test]
+    fn test_tracing() {
+        use crate::jsonrpc::telemetry::tests::Capture;
+
+        let server = MockServer::start().with_fixtures();
+        let url = server.url().replace("http://", "http://user:secret@") + "/?apikey=secret";
+        let provider = Provider::new(url);
+
+        let mut tx: Transaction = serde_json::from_value(fixtures::transaction()).unwrap();
+        tx.receipt = None;
+        crate::transaction::sign_transaction(
+            &mut tx,
+            &hex::decode("e19d05c5452598e24caad4a0d85a49146f7be089515c905ae6a19e8a578a6930")
+                .unwrap(),
+        )
+        .unwrap();
+
+        let capture = Capture::default();
+        tracing::subscriber::with_default(capture.clone(), || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(async {
+                provider.get_balance(fixtures::ADDRESS).await.unwrap();
+                provider.create_transaction(&tx).await.unwrap();
+            });
+        });
+
+        let fields = capture.0.lock().unwrap();
+        for expected in [
+            "span=rpc_call".to_string(),
+            "method=\"CreateTransaction\"".to_string(),
+            format!("endpoint={:?}", format!("{}/", server.url())),
+            "outcome=\"ok\"".to_string(),
+        ] {
+            assert!(
+                fields.contains(&expected),
+                "{} not in {:?}",
+                expected,
+                fields
+            );
+        }
+
+        // Calls are traced once, by the client.
+        assert!(!fields.iter().any(|field| field == "span=provider_call"));
+
+        let signature = tx.signature.unwrap();
+        assert!(!fields.iter().any(|field| field.contains("secret")
+            || field.contains(&signature)
+            || field.contains(&tx.sender_pub_key)));
     }
 }
@@ -0,0 +1,295 @@
+//! A blocking [`Provider`](super::Provider) for use outside of an async runtime.
+
+use futures::{Stream, StreamExt};
+use serde_json::Value;
+use tokio::runtime::Runtime;
+
+use super::Result;
+use crate::core::{
+    BalanceAndNonce, BlockListing, BlockListingEntry, BlockchainInfo, ContractValue,
+    CreateTransactionResponse, DsBlock, MinerInfo, ShardingStructure, SmartContractCode,
+    Transaction, TransactionsPage, TxBlock,
+};
+use crate::jsonrpc::blocking::new_runtime;
+use crate::jsonrpc::{PolicyError, RetryPolicy, RpcClient};
+
+/// Runs the async provider on its own single threaded runtime. It must not be used from within
+/// an async context.
+pub struct Provider {
+    inner: super::Provider,
+    runtime: Runtime,
+}
+
+impl Provider {
+    pub fn new(host: String) -> Self {
+        Self {
+            inner: super::Provider::new(host),
+            runtime: new_runtime(),
+        }
+    }
+
+    /// Fails if `policy` does not pass [`RetryPolicy::validate`].
+    pub fn with_policy(
+        host: String,
+        policy: RetryPolicy,
+    ) -> std::result::Result<Self, PolicyError> {
+        Ok(Self::with_client(RpcClient::with_policy(host, policy)?))
+    }
+
+    pub fn with_client(rpc_client: RpcClient) -> Self {
+        Self {
+            inner: super::Provider::with_client(rpc_client),
+            runtime: new_runtime(),
+        }
+    }
+
+    pub fn get_latest_tx_block(&self) -> Result<TxBlock> {
+        self.runtime.block_on(self.inner.get_latest_tx_block())
+    }
+
+    pub fn get_tx_block(&self, block_number: &str) -> Result<TxBlock> {
+        self.runtime.block_on(self.inner.get_tx_block(block_number))
+    }
+
+    pub fn get_tx_block_listing(&self, page: u32) -> Result<BlockListing> {
+        self.runtime.block_on(self.inner.get_tx_block_listing(page))
+    }
+
+    /// Walks every page of the TxBlock listing, fetching each page once the previous one has
+    /// been consumed.
+    pub fn iter_tx_block_listing(&self) -> impl Iterator<Item = Result<BlockListingEntry>> + '_ {
+        self.block_on_stream(self.inner.iter_tx_block_listing())
+    }
+
+    pub fn get_latest_ds_block(&self) -> Result<DsBlock> {
+        self.runtime.block_on(self.inner.get_latest_ds_block())
+    }
+
+    pub fn get_ds_block(&self, block_number: &str) -> Result<DsBlock> {
+        self.runtime.block_on(self.inner.get_ds_block(block_number))
+    }
+
+    pub fn get_ds_block_listing(&self, page: u32) -> Result<BlockListing> {
+        self.runtime.block_on(self.inner.get_ds_block_listing(page))
+    }
+
+    pub fn get_miner_info(&self, ds_block_number: &str) -> Result<MinerInfo> {
+        self.runtime
+            .block_on(self.inner.get_miner_info(ds_block_number))
+    }
+
+    pub fn get_transaction(&self, transaction_hash: &str) -> Result<Transaction> {
+        self.runtime
+            .block_on(self.inner.get_transaction(transaction_hash))
+    }
+
+    pub fn get_transactions_for_tx_block(&self, tx_block_number: &str) -> Result<Vec<Vec<String>>> {
+        self.runtime
+            .block_on(self.inner.get_transactions_for_tx_block(tx_block_number))
+    }
+
+    pub fn get_transactions_for_tx_block_ex(
+        &self,
+        tx_block_number: &str,
+        page: u32,
+    ) -> Result<TransactionsPage<Vec<String>>> {
+        self.runtime.block_on(
+            self.inner
+                .get_transactions_for_tx_block_ex(tx_block_number, page),
+        )
+    }
+
+    pub fn get_tx_bodies_for_tx_block(&self, tx_block_number: &str) -> Result<Vec<Transaction>> {
+        self.runtime
+            .block_on(self.inner.get_tx_bodies_for_tx_block(tx_block_number))
+    }
+
+    pub fn get_tx_bodies_for_tx_block_ex(
+        &self,
+        tx_block_number: &str,
+        page: u32,
+    ) -> Result<TransactionsPage<Transaction>> {
+        self.runtime.block_on(
+            self.inner
+                .get_tx_bodies_for_tx_block_ex(tx_block_number, page),
+        )
+    }
+
+    pub fn iter_transactions_for_tx_block<'a>(
+        &'a self,
+        tx_block_number: &'a str,
+    ) -> impl Iterator<Item = Result<String>> + 'a {
+        self.block_on_stream(self.inner.iter_transactions_for_tx_block(tx_block_number))
+    }
+
+    pub fn iter_tx_bodies_for_tx_block<'a>(
+        &'a self,
+        tx_block_number: &'a str,
+    ) -> impl Iterator<Item = Result<Transaction>> + 'a {
+        self.block_on_stream(self.inner.iter_tx_bodies_for_tx_block(tx_block_number))
+    }
+
+    pub fn get_minimum_gas_price(&self) -> Result<String> {
+        self.runtime.block_on(self.inner.get_minimum_gas_price())
+    }
+
+    pub fn get_network_id(&self) -> Result<String> {
+        self.runtime.block_on(self.inner.get_network_id())
+    }
+
+    pub fn get_blockchain_info(&self) -> Result<BlockchainInfo> {
+        self.runtime.block_on(self.inner.get_blockchain_info())
+    }
+
+    pub fn get_sharding_structure(&self) -> Result<ShardingStructure> {
+        self.runtime.block_on(self.inner.get_sharding_structure())
+    }
+
+    pub fn get_num_tx_blocks(&self) -> Result<String> {
+        self.runtime.block_on(self.inner.get_num_tx_blocks())
+    }
+
+    pub fn get_num_ds_blocks(&self) -> Result<String> {
+        self.runtime.block_on(self.inner.get_num_ds_blocks())
+    }
+
+    pub fn get_num_transactions(&self) -> Result<String> {
+        self.runtime.block_on(self.inner.get_num_transactions())
+    }
+
+    pub fn get_transaction_rate(&self) -> Result<f64> {
+        self.runtime.block_on(self.inner.get_transaction_rate())
+    }
+
+    pub fn get_tx_block_rate(&self) -> Result<f64> {
+        self.runtime.block_on(self.inner.get_tx_block_rate())
+    }
+
+    pub fn get_ds_block_rate(&self) -> Result<f64> {
+        self.runtime.block_on(self.inner.get_ds_block_rate())
+    }
+
+    pub fn get_total_coin_supply(&self) -> Result<String> {
+        self.runtime.block_on(self.inner.get_total_coin_supply())
+    }
+
+    pub fn get_prev_difficulty(&self) -> Result<u32> {
+        self.runtime.block_on(self.inner.get_prev_difficulty())
+    }
+
+    pub fn get_prev_ds_difficulty(&self) -> Result<u32> {
+        self.runtime.block_on(self.inner.get_prev_ds_difficulty())
+    }
+
+    pub fn get_smart_contract_init(&self, contract_address: &str) -> Result<Vec<ContractValue>> {
+        self.runtime
+            .block_on(self.inner.get_smart_contract_init(contract_address))
+    }
+
+    pub fn get_smart_contract_code(&self, contract_address: &str) -> Result<SmartContractCode> {
+        self.runtime
+            .block_on(self.inner.get_smart_contract_code(contract_address))
+    }
+
+    pub fn get_smart_contract_substate(
+        &self,
+        contract_address: &str,
+        variable_name: &str,
+        indices: &[&str],
+    ) -> Result<Value> {
+        self.runtime
+            .block_on(self.inner.get_smart_contract_substate(
+                contract_address,
+                variable_name,
+                indices,
+            ))
+    }
+
+    pub fn get_contract_address_from_transaction_id(&self, transaction_id: &str) -> Result<String> {
+        self.runtime.block_on(
+            self.inner
+                .get_contract_address_from_transaction_id(transaction_id),
+        )
+    }
+
+    pub fn get_balance(&self, user_address: &str) -> Result<BalanceAndNonce> {
+        self.runtime.block_on(self.inner.get_balance(user_address))
+    }
+
+    pub fn create_transaction(&self, tx: &Transaction) -> Result<CreateTransactionResponse> {
+        self.runtime.block_on(self.inner.create_transaction(tx))
+    }
+
+    pub fn batch_get_balance(
+        &self,
+        user_addresses: &[&str],
+    ) -> Result<Vec<Result<BalanceAndNonce>>> {
+        self.runtime
+            .block_on(self.inner.batch_get_balance(user_addresses))
+    }
+
+    pub fn batch_get_transaction(
+        &self,
+        transaction_hashes: &[&str],
+    ) -> Result<Vec<Result<Transaction>>> {
+        self.runtime
+            .block_on(self.inner.batch_get_transaction(transaction_hashes))
+    }
+
+    fn block_on_stream<'a, T: 'a>(
+        &'a self,
+        stream: impl Stream<Item = Result<T>> + 'a,
+    ) -> impl Iterator<Item = Result<T>> + 'a {
+        let mut stream = Box::pin(stream);
+        std::iter::from_fn(move || self.runtime.block_on(stream.next()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jsonrpc::transport::Memory;
+    use crate::mock::MockServer;
+    use serde_json::json;
+
+    #[test]
+    fn test_call_outside_runtime() {
+        let provider = Provider::new("http://127.0.0.1:1".into());
+        let err = provider.get_minimum_gas_price().unwrap_err();
+        assert_eq!(err.method.as_deref(), Some("GetMinimumGasPrice"));
+        assert_eq!(err.id, Some(1));
+        assert!(err.rpc_error().is_none());
+    }
+
+    #[test]
+    fn test_with_client() {
+        let transport = Memory::new(
+            |request| json!({"id": request["id"], "jsonrpc": "2.0", "result": "2000000000"}),
+        );
+        let provider = Provider::with_client(
+            RpcClient::with_transport(transport, RetryPolicy::no_retry()).unwrap(),
+        );
+
+        assert_eq!(provider.get_minimum_gas_price().unwrap(), "2000000000");
+    }
+
+    #[test]
+    fn test_iter_tx_block_listing() {
+        let server = MockServer::start().with_fixtures();
+        server
+            .mock("TxBlockListing")
+            .with_params(json!([1]))
+            .returns(json!({"data": [{"BlockNum": 2, "Hash": "b"}], "maxPages": 2}));
+        server
+            .mock("TxBlockListing")
+            .with_params(json!([2]))
+            .returns(json!({"data": [{"BlockNum": 1, "Hash": "a"}], "maxPages": 2}));
+
+        let provider = Provider::new(server.url());
+        let blocks: Vec<u64> = provider
+            .iter_tx_block_listing()
+            .map(|entry| entry.unwrap().block_num)
+            .collect();
+        assert_eq!(blocks, vec![2, 1]);
+    }
+}
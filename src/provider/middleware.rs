@@ -0,0 +1,614 @@
+//! Composable layers around [`Provider`].
+//!
+//! A middleware wraps an inner middleware and overrides the methods it cares about, everything
+//! else is passed through. Sending a transaction goes through two steps: layers fill in the
+//! fields they own in [`Middleware::send_transaction`], and [`SignerMiddleware`] signs the
+//! result and hands it down with [`Middleware::create_transaction`]. Layers below the signer are
+//! asked to fill in their fields through [`Middleware::fill_transaction`] before signing, so the
+//! layers can be stacked in any order.
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::{future, StreamExt, TryStreamExt};
+use serde_json::Value;
+
+use super::{paginate, Provider, ProviderError, Result};
+use crate::core::{
+    BalanceAndNonce, BlockListing, BlockListingEntry, BlockchainInfo, ContractValue,
+    CreateTransactionResponse, DsBlock, MinerInfo, ShardingStructure, SmartContractCode,
+    Transaction, TransactionsPage, TxBlock,
+};
+
+mod cache;
+mod gas_price;
+mod logging;
+mod nonce;
+mod signer;
+
+pub use self::cache::{CacheMiddleware, ResponseCache};
+pub use self::gas_price::GasPriceMiddleware;
+pub use self::logging::LoggingMiddleware;
+pub use self::nonce::NonceManagerMiddleware;
+pub use self::signer::SignerMiddleware;
+
+/// Returns the layer below `middleware`, for the methods it passes through.
+fn next<M: Middleware + ?Sized>(middleware: &M) -> Result<&M::Inner> {
+    middleware.inner().ok_or_else(|| {
+        ProviderError::middleware("the innermost layer does not implement this method")
+    })
+}
+
+/// The node answers paged requests for blocks without transactions with an error, so the
+/// iterators check the header first.
+/// Returns whether the node turned down a transactions query because the block is empty, which
+/// it reports as an error rather than an empty first page.
+fn is_empty_tx_block(err: &ProviderError) -> bool {
+    err.rpc_error()
+        .is_some_and(|err| err.message == "TxBlock has no transactions")
+}
+
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    type Inner: Middleware;
+
+    /// Returns the layer this one wraps, or `None` for the innermost layer, which has to
+    /// implement every method itself.
+    fn inner(&self) -> Option<&Self::Inner>;
+
+    async fn get_latest_tx_block(&self) -> Result<TxBlock> {
+        next(self)?.get_latest_tx_block().await
+    }
+
+    async fn get_tx_block(&self, block_number: &str) -> Result<TxBlock> {
+        next(self)?.get_tx_block(block_number).await
+    }
+
+    async fn get_tx_block_listing(&self, page: u32) -> Result<BlockListing> {
+        next(self)?.get_tx_block_listing(page).await
+    }
+
+    async fn get_latest_ds_block(&self) -> Result<DsBlock> {
+        next(self)?.get_latest_ds_block().await
+    }
+
+    async fn get_ds_block(&self, block_number: &str) -> Result<DsBlock> {
+        next(self)?.get_ds_block(block_number).await
+    }
+
+    async fn get_ds_block_listing(&self, page: u32) -> Result<BlockListing> {
+        next(self)?.get_ds_block_listing(page).await
+    }
+
+    async fn get_miner_info(&self, ds_block_number: &str) -> Result<MinerInfo> {
+        next(self)?.get_miner_info(ds_block_number).await
+    }
+
+    async fn get_transaction(&self, transaction_hash: &str) -> Result<Transaction> {
+        next(self)?.get_transaction(transaction_hash).await
+    }
+
+    async fn get_transactions_for_tx_block(
+        &self,
+        tx_block_number: &str,
+    ) -> Result<Vec<Vec<String>>> {
+        next(self)?
+            .get_transactions_for_tx_block(tx_block_number)
+            .await
+    }
+
+    async fn get_transactions_for_tx_block_ex(
+        &self,
+        tx_block_number: &str,
+        page: u32,
+    ) -> Result<TransactionsPage<Vec<String>>> {
+        next(self)?
+            .get_transactions_for_tx_block_ex(tx_block_number, page)
+            .await
+    }
+
+    async fn get_tx_bodies_for_tx_block(&self, tx_block_number: &str) -> Result<Vec<Transaction>> {
+        next(self)?
+            .get_tx_bodies_for_tx_block(tx_block_number)
+            .await
+    }
+
+    async fn get_tx_bodies_for_tx_block_ex(
+        &self,
+        tx_block_number: &str,
+        page: u32,
+    ) -> Result<TransactionsPage<Transaction>> {
+        next(self)?
+            .get_tx_bodies_for_tx_block_ex(tx_block_number, page)
+            .await
+    }
+
+    /// Walks every page of [`get_tx_block_listing`](Self::get_tx_block_listing), newest block
+    /// first. Blocks produced during the walk shift the pages back; entries already yielded are
+    /// skipped, so every block is yielded once and blocks newer than the first are left out.
+    fn iter_tx_block_listing(&self) -> BoxStream<'_, Result<BlockListingEntry>> {
+        let mut last = None;
+        paginate(1, move |page| async move {
+            let listing = self.get_tx_block_listing(page).await?;
+            Ok((listing.data, listing.max_pages + 1))
+        })
+        .try_filter(move |entry| {
+            let new = last.iter().all(|last| entry.block_num < *last);
+            if new {
+                last = Some(entry.block_num);
+            }
+            future::ready(new)
+        })
+        .boxed()
+    }
+
+    /// Walks every page of
+    /// [`get_transactions_for_tx_block_ex`](Self::get_transactions_for_tx_block_ex), yielding
+    /// the hashes of all micro blocks in order.
+    fn iter_transactions_for_tx_block<'a>(
+        &'a self,
+        tx_block_number: &'a str,
+    ) -> BoxStream<'a, Result<String>> {
+        paginate(0, move |page| async move {
+            match self
+                .get_transactions_for_tx_block_ex(tx_block_number, page)
+                .await
+            {
+                Ok(page) => Ok((
+                    page.transactions.into_iter().flatten().collect(),
+                    page.num_pages,
+                )),
+                Err(err) if page == 0 && is_empty_tx_block(&err) => Ok((Vec::new(), 0)),
+                Err(err) => Err(err),
+            }
+        })
+        .boxed()
+    }
+
+    /// Walks every page of [`get_tx_bodies_for_tx_block_ex`](Self::get_tx_bodies_for_tx_block_ex).
+    fn iter_tx_bodies_for_tx_block<'a>(
+        &'a self,
+        tx_block_number: &'a str,
+    ) -> BoxStream<'a, Result<Transaction>> {
+        paginate(0, move |page| async move {
+            match self
+                .get_tx_bodies_for_tx_block_ex(tx_block_number, page)
+                .await
+            {
+                Ok(page) => Ok((page.transactions, page.num_pages)),
+                Err(err) if page == 0 && is_empty_tx_block(&err) => Ok((Vec::new(), 0)),
+                Err(err) => Err(err),
+            }
+        })
+        .boxed()
+    }
+
+    async fn get_minimum_gas_price(&self) -> Result<String> {
+        next(self)?.get_minimum_gas_price().await
+    }
+
+    async fn get_network_id(&self) -> Result<String> {
+        next(self)?.get_network_id().await
+    }
+
+    async fn get_blockchain_info(&self) -> Result<BlockchainInfo> {
+        next(self)?.get_blockchain_info().await
+    }
+
+    async fn get_sharding_structure(&self) -> Result<ShardingStructure> {
+        next(self)?.get_sharding_structure().await
+    }
+
+    async fn get_num_tx_blocks(&self) -> Result<String> {
+        next(self)?.get_num_tx_blocks().await
+    }
+
+    async fn get_num_ds_blocks(&self) -> Result<String> {
+        next(self)?.get_num_ds_blocks().await
+    }
+
+    async fn get_num_transactions(&self) -> Result<String> {
+        next(self)?.get_num_transactions().await
+    }
+
+    async fn get_transaction_rate(&self) -> Result<f64> {
+        next(self)?.get_transaction_rate().await
+    }
+
+    async fn get_tx_block_rate(&self) -> Result<f64> {
+        next(self)?.get_tx_block_rate().await
+    }
+
+    async fn get_ds_block_rate(&self) -> Result<f64> {
+        next(self)?.get_ds_block_rate().await
+    }
+
+    async fn get_total_coin_supply(&self) -> Result<String> {
+        next(self)?.get_total_coin_supply().await
+    }
+
+    async fn get_prev_difficulty(&self) -> Result<u32> {
+        next(self)?.get_prev_difficulty().await
+    }
+
+    async fn get_prev_ds_difficulty(&self) -> Result<u32> {
+        next(self)?.get_prev_ds_difficulty().await
+    }
+
+    async fn get_smart_contract_init(&self, contract_address: &str) -> Result<Vec<ContractValue>> {
+        next(self)?.get_smart_contract_init(contract_address).await
+    }
+
+    async fn get_smart_contract_code(&self, contract_address: &str) -> Result<SmartContractCode> {
+        next(self)?.get_smart_contract_code(contract_address).await
+    }
+
+    async fn get_smart_contract_substate(
+        &self,
+        contract_address: &str,
+        variable_name: &str,
+        indices: &[&str],
+    ) -> Result<Value> {
+        next(self)?
+            .get_smart_contract_substate(contract_address, variable_name, indices)
+            .await
+    }
+
+    async fn get_contract_address_from_transaction_id(
+        &self,
+        transaction_id: &str,
+    ) -> Result<String> {
+        next(self)?
+            .get_contract_address_from_transaction_id(transaction_id)
+            .await
+    }
+
+    async fn get_balance(&self, user_address: &str) -> Result<BalanceAndNonce> {
+        next(self)?.get_balance(user_address).await
+    }
+
+    async fn batch_get_balance(
+        &self,
+        user_addresses: &[&str],
+    ) -> Result<Vec<Result<BalanceAndNonce>>> {
+        next(self)?.batch_get_balance(user_addresses).await
+    }
+
+    async fn batch_get_transaction(
+        &self,
+        transaction_hashes: &[&str],
+    ) -> Result<Vec<Result<Transaction>>> {
+        next(self)?.batch_get_transaction(transaction_hashes).await
+    }
+
+    /// Fills in the fields of `tx` this layer and the layers below it are responsible for.
+    async fn fill_transaction(&self, tx: &mut Transaction) -> Result<()> {
+        next(self)?.fill_transaction(tx).await
+    }
+
+    /// Fills in, signs and sends `tx`. Without a [`SignerMiddleware`] in the stack `tx` must
+    /// already be signed.
+    async fn send_transaction(&self, tx: Transaction) -> Result<CreateTransactionResponse> {
+        next(self)?.send_transaction(tx).await
+    }
+
+    /// Sends a signed transaction as is.
+    async fn create_transaction(&self, tx: &Transaction) -> Result<CreateTransactionResponse> {
+        next(self)?.create_transaction(tx).await
+    }
+}
+
+#[async_trait]
+impl Middleware for Provider {
+    type Inner = Self;
+
+    fn inner(&self) -> Option<&Self::Inner> {
+        None
+    }
+
+    async fn get_latest_tx_block(&self) -> Result<TxBlock> {
+        Provider::get_latest_tx_block(self).await
+    }
+
+    async fn get_tx_block(&self, block_number: &str) -> Result<TxBlock> {
+        Provider::get_tx_block(self, block_number).await
+    }
+
+    async fn get_tx_block_listing(&self, page: u32) -> Result<BlockListing> {
+        Provider::get_tx_block_listing(self, page).await
+    }
+
+    async fn get_latest_ds_block(&self) -> Result<DsBlock> {
+        Provider::get_latest_ds_block(self).await
+    }
+
+    async fn get_ds_block(&self, block_number: &str) -> Result<DsBlock> {
+        Provider::get_ds_block(self, block_number).await
+    }
+
+    async fn get_ds_block_listing(&self, page: u32) -> Result<BlockListing> {
+        Provider::get_ds_block_listing(self, page).await
+    }
+
+    async fn get_miner_info(&self, ds_block_number: &str) -> Result<MinerInfo> {
+        Provider::get_miner_info(self, ds_block_number).await
+    }
+
+    async fn get_transaction(&self, transaction_hash: &str) -> Result<Transaction> {
+        Provider::get_transaction(self, transaction_hash).await
+    }
+
+    async fn get_transactions_for_tx_block(
+        &self,
+        tx_block_number: &str,
+    ) -> Result<Vec<Vec<String>>> {
+        Provider::get_transactions_for_tx_block(self, tx_block_number).await
+    }
+
+    async fn get_transactions_for_tx_block_ex(
+        &self,
+        tx_block_number: &str,
+        page: u32,
+    ) -> Result<TransactionsPage<Vec<String>>> {
+        Provider::get_transactions_for_tx_block_ex(self, tx_block_number, page).await
+    }
+
+    async fn get_tx_bodies_for_tx_block(&self, tx_block_number: &str) -> Result<Vec<Transaction>> {
+        Provider::get_tx_bodies_for_tx_block(self, tx_block_number).await
+    }
+
+    async fn get_tx_bodies_for_tx_block_ex(
+        &self,
+        tx_block_number: &str,
+        page: u32,
+    ) -> Result<TransactionsPage<Transaction>> {
+        Provider::get_tx_bodies_for_tx_block_ex(self, tx_block_number, page).await
+    }
+
+    async fn get_minimum_gas_price(&self) -> Result<String> {
+        Provider::get_minimum_gas_price(self).await
+    }
+
+    async fn get_network_id(&self) -> Result<String> {
+        Provider::get_network_id(self).await
+    }
+
+    async fn get_blockchain_info(&self) -> Result<BlockchainInfo> {
+        Provider::get_blockchain_info(self).await
+    }
+
+    async fn get_sharding_structure(&self) -> Result<ShardingStructure> {
+        Provider::get_sharding_structure(self).await
+    }
+
+    async fn get_num_tx_blocks(&self) -> Result<String> {
+        Provider::get_num_tx_blocks(self).await
+    }
+
+    async fn get_num_ds_blocks(&self) -> Result<String> {
+        Provider::get_num_ds_blocks(self).await
+    }
+
+    async fn get_num_transactions(&self) -> Result<String> {
+        Provider::get_num_transactions(self).await
+    }
+
+    async fn get_transaction_rate(&self) -> Result<f64> {
+        Provider::get_transaction_rate(self).await
+    }
+
+    async fn get_tx_block_rate(&self) -> Result<f64> {
+        Provider::get_tx_block_rate(self).await
+    }
+
+    async fn get_ds_block_rate(&self) -> Result<f64> {
+        Provider::get_ds_block_rate(self).await
+    }
+
+    async fn get_total_coin_supply(&self) -> Result<String> {
+        Provider::get_total_coin_supply(self).await
+    }
+
+    async fn get_prev_difficulty(&self) -> Result<u32> {
+        Provider::get_prev_difficulty(self).await
+    }
+
+    async fn get_prev_ds_difficulty(&self) -> Result<u32> {
+        Provider::get_prev_ds_difficulty(self).await
+    }
+
+    async fn get_smart_contract_init(&self, contract_address: &str) -> Result<Vec<ContractValue>> {
+        Provider::get_smart_contract_init(self, contract_address).await
+    }
+
+    async fn get_smart_contract_code(&self, contract_address: &str) -> Result<SmartContractCode> {
+        Provider::get_smart_contract_code(self, contract_address).await
+    }
+
+    async fn get_smart_contract_substate(
+        &self,
+        contract_address: &str,
+        variable_name: &str,
+        indices: &[&str],
+    ) -> Result<Value> {
+        Provider::get_smart_contract_substate(self, contract_address, variable_name, indices).await
+    }
+
+    async fn get_contract_address_from_transaction_id(
+        &self,
+        transaction_id: &str,
+    ) -> Result<String> {
+        Provider::get_contract_address_from_transaction_id(self, transaction_id).await
+    }
+
+    async fn get_balance(&self, user_address: &str) -> Result<BalanceAndNonce> {
+        Provider::get_balance(self, user_address).await
+    }
+
+    async fn batch_get_balance(
+        &self,
+        user_addresses: &[&str],
+    ) -> Result<Vec<Result<BalanceAndNonce>>> {
+        Provider::batch_get_balance(self, user_addresses).await
+    }
+
+    async fn batch_get_transaction(
+        &self,
+        transaction_hashes: &[&str],
+    ) -> Result<Vec<Result<Transaction>>> {
+        Provider::batch_get_transaction(self, transaction_hashes).await
+    }
+
+    async fn fill_transaction(&self, _tx: &mut Transaction) -> Result<()> {
+        Ok(())
+    }
+
+    async fn send_transaction(&self, tx: Transaction) -> Result<CreateTransactionResponse> {
+        Provider::create_transaction(self, &tx).await
+    }
+
+    async fn create_transaction(&self, tx: &Transaction) -> Result<CreateTransactionResponse> {
+        Provider::create_transaction(self, tx).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::account::wallet::Wallet;
+    use crate::crypto::schnorr::{verify, Signature};
+    use crate::jsonrpc::transport::Memory;
+    use crate::jsonrpc::{RetryPolicy, RpcClient};
+    use crate::test_support;
+    use crate::transaction::encode_transaction_proto;
+
+    const PRIVATE_KEY: &str = "e19d05c5452598e24caad4a0d85a49146f7be089515c905ae6a19e8a578a6930";
+
+    /// A provider answering from a fake node, and the transactions the node received.
+    fn provider() -> (Provider, Arc<Mutex<Vec<Value>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let node_received = received.clone();
+
+        let transport = Memory::new(move |request| {
+            let result = match request["method"].as_str().unwrap() {
+                "GetBalance" => json!({"balance": "1000000000000", "nonce": 5}),
+                "GetMinimumGasPrice" => json!("2000000000"),
+                "CreateTransaction" => {
+                    node_received
+                        .lock()
+                        .unwrap()
+                        .push(request["params"][0].clone());
+                    json!({"Info": "Non-contract txn, sent to shard", "TranID": "abcd"})
+                }
+                method => panic!("unexpected method {}", method),
+            };
+
+            json!({"id": request["id"], "jsonrpc": "2.0", "result": result})
+        });
+
+        let provider = Provider::with_client(
+            RpcClient::with_transport(transport, RetryPolicy::no_retry()).unwrap(),
+        );
+        (provider, received)
+    }
+
+    fn transaction() -> Transaction {
+        Transaction {
+            sender_pub_key: String::new(),
+            gas_price: String::new(),
+            nonce: None,
+            ..test_support::payment()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stack() {
+        let (provider, received) = provider();
+        let wallet = Wallet::new();
+        wallet.add_by_private_key(PRIVATE_KEY).unwrap();
+        let address = wallet.default_account().unwrap().address().to_string();
+
+        // The signer sits in the middle, so the nonce is filled above it and the gas price below.
+        let middleware = LoggingMiddleware::new(NonceManagerMiddleware::new(
+            SignerMiddleware::new(GasPriceMiddleware::new(provider), wallet),
+            &address,
+        ));
+
+        for _ in 0..2 {
+            let response = middleware.send_transaction(transaction()).await.unwrap();
+            assert_eq!(response.tran_id, "abcd");
+        }
+
+        let received = received.lock().unwrap();
+        assert_eq!(received[0]["nonce"], json!(6));
+        assert_eq!(received[1]["nonce"], json!(7));
+        assert_eq!(received[0]["gasPrice"], json!("2000000000"));
+        assert_eq!(received[0]["toAddr"], json!(test_support::RECIPIENT));
+
+        let mut tx = transaction();
+        tx.nonce = Some("6".into());
+        tx.gas_price = "2000000000".into();
+        tx.sender_pub_key = received[0]["pubKey"].as_str().unwrap().to_string();
+        let signature = Signature::from_hex(received[0]["signature"].as_str().unwrap()).unwrap();
+        assert!(verify(
+            &encode_transaction_proto(&tx).unwrap(),
+            &hex::decode(&tx.sender_pub_key).unwrap(),
+            &signature
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_logging_redacts_endpoint() {
+        let capture = crate::jsonrpc::telemetry::tests::Capture::default();
+        let _guard = tracing::subscriber::set_default(capture.clone());
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://user:secret@{}/?apikey=secret",
+            listener.local_addr().unwrap()
+        );
+        drop(listener);
+        let provider = Provider::with_policy(url, RetryPolicy::no_retry()).unwrap();
+
+        let err = LoggingMiddleware::new(provider)
+            .get_network_id()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("secret"));
+
+        // Credentials are kept out of the logs.
+        let fields = capture.0.lock().unwrap();
+        assert!(fields.iter().any(|field| field.starts_with("err=")));
+        assert!(!fields.iter().any(|field| field.contains("secret")));
+    }
+
+    #[tokio::test]
+    async fn test_send_unsigned_without_signer() {
+        let (provider, received) = provider();
+        let middleware = GasPriceMiddleware::new(provider);
+
+        let mut tx = transaction();
+        tx.nonce = Some("1".into());
+        let err = middleware.send_transaction(tx).await.unwrap_err();
+        assert_eq!(err.method.as_deref(), Some("CreateTransaction"));
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_innermost_layer_without_method() {
+        struct Bare;
+
+        impl Middleware for Bare {
+            type Inner = Provider;
+
+            fn inner(&self) -> Option<&Provider> {
+                None
+            }
+        }
+
+        assert!(Bare.get_network_id().await.is_err());
+    }
+}
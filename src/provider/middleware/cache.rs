@@ -0,0 +1,318 @@
+use std::fs;
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use lru::LruCache;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+use super::Middleware;
+use crate::core::{ContractValue, DsBlock, SmartContractCode, Transaction, TxBlock};
+use crate::provider::Result;
+use crate::util::{normalize_address, sha_256};
+
+/// An LRU bounded store for responses, optionally backed by a directory on disk. Only ever holds
+/// data that can not change once it is on chain, so entries never expire.
+pub struct ResponseCache {
+    memory: Mutex<LruCache<String, Value>>,
+    dir: Option<PathBuf>,
+}
+
+impl ResponseCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            memory: Mutex::new(LruCache::new(capacity)),
+            dir: None,
+        }
+    }
+
+    /// Keeps up to `capacity` entries in memory and every entry in `dir`, so that the cache
+    /// survives restarts. The disk cache is not bounded.
+    pub fn with_dir(capacity: NonZeroUsize, dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            memory: Mutex::new(LruCache::new(capacity)),
+            dir: Some(dir.as_ref().to_path_buf()),
+        })
+    }
+
+    /// Returns the number of entries held in memory.
+    pub fn len(&self) -> usize {
+        self.memory.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Empties the in memory cache. Entries on disk are kept.
+    pub fn clear(&self) {
+        self.memory.lock().unwrap().clear();
+    }
+
+    fn get(&self, key: &str) -> Option<Value> {
+        if let Some(value) = self.memory.lock().unwrap().get(key) {
+            return Some(value.clone());
+        }
+
+        let value: Value = serde_json::from_slice(&fs::read(self.path(key)?).ok()?).ok()?;
+        self.memory
+            .lock()
+            .unwrap()
+            .put(key.to_string(), value.clone());
+
+        Some(value)
+    }
+
+    fn put(&self, key: &str, value: Value) {
+        if let Some(path) = self.path(key) {
+            if let Err(err) = write_atomically(&path, value.to_string()) {
+                warn!(path = %path.display(), %err, "failed to write cache entry");
+            }
+        }
+
+        self.memory.lock().unwrap().put(key.to_string(), value);
+    }
+
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        Some(dir.join(format!("{}.json", hex::encode(sha_256(key.as_bytes())))))
+    }
+}
+
+/// Writes `contents` to a unique sibling of `path` and renames it into place, so that readers
+/// and crashes never see a partial entry.
+fn write_atomically(path: &Path, contents: String) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", uuid::Uuid::new_v4()));
+    let tmp = PathBuf::from(tmp);
+
+    let result = fs::write(&tmp, contents).and_then(|_| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+
+    result
+}
+
+/// Caches immutable chain data: confirmed transactions, blocks by number and contract code and
+/// init parameters. Anything depending on the chain head goes straight to the inner layer.
+pub struct CacheMiddleware<M> {
+    inner: M,
+    cache: ResponseCache,
+}
+
+impl<M: Middleware> CacheMiddleware<M> {
+    pub fn new(inner: M, cache: ResponseCache) -> Self {
+        Self { inner, cache }
+    }
+
+    pub fn cache(&self) -> &ResponseCache {
+        &self.cache
+    }
+
+    async fn cached<T, F>(&self, key: String, fetch: F, cacheable: fn(&T) -> bool) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = Result<T>>,
+    {
+        if let Some(value) = self.cache.get(&key) {
+            if let Ok(value) = serde_json::from_value(value) {
+                return Ok(value);
+            }
+        }
+
+        let value = fetch.await?;
+        if cacheable(&value) {
+            if let Ok(json) = serde_json::to_value(&value) {
+                self.cache.put(&key, json);
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+/// Keys blocks by their parsed number, so that e.g. `"01"` and `"1"` share an entry.
+fn block_key(method: &str, block_number: &str) -> String {
+    match block_number.parse::<u64>() {
+        Ok(block_number) => format!("{}:{}", method, block_number),
+        Err(_) => format!("{}:{}", method, block_number),
+    }
+}
+
+fn address_key(method: &str, address: &str) -> String {
+    let address = normalize_address(address).unwrap_or_else(|_| address.to_lowercase());
+    format!("{}:{}", method, address)
+}
+
+#[async_trait]
+impl<M: Middleware> Middleware for CacheMiddleware<M> {
+    type Inner = M;
+
+    fn inner(&self) -> Option<&M> {
+        Some(&self.inner)
+    }
+
+    async fn get_tx_block(&self, block_number: &str) -> Result<TxBlock> {
+        self.cached(
+            block_key("GetTxBlock", block_number),
+            self.inner.get_tx_block(block_number),
+            |_| true,
+        )
+        .await
+    }
+
+    async fn get_ds_block(&self, block_number: &str) -> Result<DsBlock> {
+        self.cached(
+            block_key("GetDsBlock", block_number),
+            self.inner.get_ds_block(block_number),
+            |_| true,
+        )
+        .await
+    }
+
+    async fn get_transaction(&self, transaction_hash: &str) -> Result<Transaction> {
+        let key = format!(
+            "GetTransaction:{}",
+            transaction_hash.trim_start_matches("0x").to_lowercase()
+        );
+
+        // Only confirmed transactions come with a receipt.
+        self.cached(key, self.inner.get_transaction(transaction_hash), |tx| {
+            tx.receipt.is_some()
+        })
+        .await
+    }
+
+    async fn get_smart_contract_init(&self, contract_address: &str) -> Result<Vec<ContractValue>> {
+        self.cached(
+            address_key("GetSmartContractInit", contract_address),
+            self.inner.get_smart_contract_init(contract_address),
+            |_| true,
+        )
+        .await
+    }
+
+    async fn get_smart_contract_code(&self, contract_address: &str) -> Result<SmartContractCode> {
+        self.cached(
+            address_key("GetSmartContractCode", contract_address),
+            self.inner.get_smart_contract_code(contract_address),
+            |_| true,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::jsonrpc::transport::Memory;
+    use crate::jsonrpc::{RetryPolicy, RpcClient};
+    use crate::mock::fixtures;
+    use crate::provider::Provider;
+
+    const ADDRESS: &str = "9611c53BE6d1b32058b2747bdeCECed7e1216793";
+
+    fn provider(calls: &Arc<AtomicUsize>) -> Provider {
+        let calls = calls.clone();
+        let transport = Memory::new(move |request| {
+            calls.fetch_add(1, Ordering::Relaxed);
+
+            let result = match request["method"].as_str().unwrap() {
+                "GetSmartContractCode" => json!({"code": "scilla_version 0"}),
+                "GetBalance" => json!({"balance": "0", "nonce": 1}),
+                "GetTxBlock" => fixtures::tx_block(),
+                "GetTransaction" if request["params"][0] == "aa" => json!({
+                    "ID": "aa", "amount": "0", "gasLimit": "50", "gasPrice": "2000000000",
+                    "senderPubKey": "", "toAddr": ADDRESS, "version": "21823489",
+                    "priority": false, "receipt": {
+                        "cumulative_gas": "1", "epoch_num": "1", "success": true
+                    }
+                }),
+                "GetTransaction" => json!({
+                    "ID": "bb", "amount": "0", "gasLimit": "50", "gasPrice": "2000000000",
+                    "senderPubKey": "", "toAddr": ADDRESS, "version": "21823489",
+                    "priority": false
+                }),
+                method => panic!("unexpected method {}", method),
+            };
+
+            json!({"id": request["id"], "jsonrpc": "2.0", "result": result})
+        });
+
+        Provider::with_client(
+            RpcClient::with_transport(transport, RetryPolicy::no_retry()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_cache() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let middleware = CacheMiddleware::new(
+            provider(&calls),
+            ResponseCache::new(NonZeroUsize::new(8).unwrap()),
+        );
+
+        for address in [ADDRESS, &ADDRESS.to_lowercase(), &format!("0x{}", ADDRESS)] {
+            let code = middleware.get_smart_contract_code(address).await.unwrap();
+            assert_eq!(code.code, "scilla_version 0");
+        }
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // Head dependent calls and unconfirmed transactions always go to the node.
+        for _ in 0..2 {
+            middleware.get_balance(ADDRESS).await.unwrap();
+            middleware.get_transaction("bb").await.unwrap();
+            middleware.get_transaction("aa").await.unwrap();
+        }
+        assert_eq!(calls.load(Ordering::Relaxed), 6);
+        assert_eq!(middleware.cache().len(), 2);
+
+        for block_number in ["1442201", "01442201"] {
+            middleware.get_tx_block(block_number).await.unwrap();
+        }
+        assert_eq!(calls.load(Ordering::Relaxed), 7);
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache() {
+        let dir = std::env::temp_dir().join(format!("zilliqa-cache-{}", uuid::Uuid::new_v4()));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let capacity = NonZeroUsize::new(1).unwrap();
+
+        let middleware = CacheMiddleware::new(
+            provider(&calls),
+            ResponseCache::with_dir(capacity, &dir).unwrap(),
+        );
+        middleware.get_smart_contract_code(ADDRESS).await.unwrap();
+
+        let middleware = CacheMiddleware::new(
+            provider(&calls),
+            ResponseCache::with_dir(capacity, &dir).unwrap(),
+        );
+        middleware.get_smart_contract_code(ADDRESS).await.unwrap();
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // Entries are renamed into place, without leftover temporary files.
+        let entries: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].ends_with(".json"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
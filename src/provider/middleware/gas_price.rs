@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+
+use super::Middleware;
+use crate::core::{CreateTransactionResponse, Transaction};
+use crate::provider::Result;
+
+/// Fills in a missing `gas_price` with the network's minimum gas price.
+pub struct GasPriceMiddleware<M> {
+    inner: M,
+}
+
+impl<M: Middleware> GasPriceMiddleware<M> {
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+
+    async fn fill_gas_price(&self, tx: &mut Transaction) -> Result<()> {
+        if tx.gas_price.is_empty() {
+            tx.gas_price = self.inner.get_minimum_gas_price().await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<M: Middleware> Middleware for GasPriceMiddleware<M> {
+    type Inner = M;
+
+    fn inner(&self) -> Option<&M> {
+        Some(&self.inner)
+    }
+
+    async fn fill_transaction(&self, tx: &mut Transaction) -> Result<()> {
+        self.fill_gas_price(tx).await?;
+        self.inner.fill_transaction(tx).await
+    }
+
+    async fn send_transaction(&self, mut tx: Transaction) -> Result<CreateTransactionResponse> {
+        self.fill_gas_price(&mut tx).await?;
+        self.inner.send_transaction(tx).await
+    }
+}
@@ -0,0 +1,300 @@
+use std::future::Future;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use super::Middleware;
+use crate::core::{
+    BalanceAndNonce, BlockListing, BlockchainInfo, ContractValue, CreateTransactionResponse,
+    DsBlock, MinerInfo, ShardingStructure, SmartContractCode, Transaction, TransactionsPage,
+    TxBlock,
+};
+use crate::jsonrpc::transport::redact_url;
+use crate::provider::{ProviderError, ProviderErrorSource, Result};
+
+const TARGET: &str = "zilliqa_sdk::provider";
+
+/// Logs every call with its duration through `tracing`: successes at debug level and failures
+/// at warn level.
+pub struct LoggingMiddleware<M> {
+    inner: M,
+}
+
+impl<M: Middleware> LoggingMiddleware<M> {
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+
+    async fn log<T, F: Future<Output = Result<T>>>(&self, method: &str, call: F) -> Result<T> {
+        let start = Instant::now();
+        let result = call.await;
+
+        let duration_ms = start.elapsed().as_millis() as u64;
+        match &result {
+            Ok(_) => debug!(target: TARGET, method, duration_ms, "call succeeded"),
+            Err(err) => {
+                let err = redact(err);
+                warn!(target: TARGET, method, duration_ms, %err, "call failed")
+            }
+        }
+
+        result
+    }
+}
+
+/// Returns the message of `err` with the endpoint redacted, as `reqwest` errors name the URL,
+/// which may hold API keys.
+fn redact(err: &ProviderError) -> String {
+    let message = err.to_string();
+    match &err.source {
+        ProviderErrorSource::Reqwest(source) => match source.url() {
+            Some(url) => {
+                message.replace(url.as_str(), &redact_url(url.as_str()).unwrap_or_default())
+            }
+            None => message,
+        },
+        _ => message,
+    }
+}
+
+#[async_trait]
+impl<M: Middleware> Middleware for LoggingMiddleware<M> {
+    type Inner = M;
+
+    fn inner(&self) -> Option<&M> {
+        Some(&self.inner)
+    }
+
+    async fn get_latest_tx_block(&self) -> Result<TxBlock> {
+        self.log("GetLatestTxBlock", self.inner.get_latest_tx_block())
+            .await
+    }
+
+    async fn get_tx_block(&self, block_number: &str) -> Result<TxBlock> {
+        self.log("GetTxBlock", self.inner.get_tx_block(block_number))
+            .await
+    }
+
+    async fn get_tx_block_listing(&self, page: u32) -> Result<BlockListing> {
+        self.log("TxBlockListing", self.inner.get_tx_block_listing(page))
+            .await
+    }
+
+    async fn get_latest_ds_block(&self) -> Result<DsBlock> {
+        self.log("GetLatestDsBlock", self.inner.get_latest_ds_block())
+            .await
+    }
+
+    async fn get_ds_block(&self, block_number: &str) -> Result<DsBlock> {
+        self.log("GetDsBlock", self.inner.get_ds_block(block_number))
+            .await
+    }
+
+    async fn get_ds_block_listing(&self, page: u32) -> Result<BlockListing> {
+        self.log("DSBlockListing", self.inner.get_ds_block_listing(page))
+            .await
+    }
+
+    async fn get_miner_info(&self, ds_block_number: &str) -> Result<MinerInfo> {
+        self.log("GetMinerInfo", self.inner.get_miner_info(ds_block_number))
+            .await
+    }
+
+    async fn get_transaction(&self, transaction_hash: &str) -> Result<Transaction> {
+        self.log(
+            "GetTransaction",
+            self.inner.get_transaction(transaction_hash),
+        )
+        .await
+    }
+
+    async fn get_transactions_for_tx_block(
+        &self,
+        tx_block_number: &str,
+    ) -> Result<Vec<Vec<String>>> {
+        self.log(
+            "GetTransactionsForTxBlock",
+            self.inner.get_transactions_for_tx_block(tx_block_number),
+        )
+        .await
+    }
+
+    async fn get_transactions_for_tx_block_ex(
+        &self,
+        tx_block_number: &str,
+        page: u32,
+    ) -> Result<TransactionsPage<Vec<String>>> {
+        self.log(
+            "GetTransactionsForTxBlockEx",
+            self.inner
+                .get_transactions_for_tx_block_ex(tx_block_number, page),
+        )
+        .await
+    }
+
+    async fn get_tx_bodies_for_tx_block(&self, tx_block_number: &str) -> Result<Vec<Transaction>> {
+        self.log(
+            "GetTxBodiesForTxBlock",
+            self.inner.get_tx_bodies_for_tx_block(tx_block_number),
+        )
+        .await
+    }
+
+    async fn get_tx_bodies_for_tx_block_ex(
+        &self,
+        tx_block_number: &str,
+        page: u32,
+    ) -> Result<TransactionsPage<Transaction>> {
+        self.log(
+            "GetTxBodiesForTxBlockEx",
+            self.inner
+                .get_tx_bodies_for_tx_block_ex(tx_block_number, page),
+        )
+        .await
+    }
+
+    async fn get_minimum_gas_price(&self) -> Result<String> {
+        self.log("GetMinimumGasPrice", self.inner.get_minimum_gas_price())
+            .await
+    }
+
+    async fn get_network_id(&self) -> Result<String> {
+        self.log("GetNetworkId", self.inner.get_network_id()).await
+    }
+
+    async fn get_blockchain_info(&self) -> Result<BlockchainInfo> {
+        self.log("GetBlockchainInfo", self.inner.get_blockchain_info())
+            .await
+    }
+
+    async fn get_sharding_structure(&self) -> Result<ShardingStructure> {
+        self.log("GetShardingStructure", self.inner.get_sharding_structure())
+            .await
+    }
+
+    async fn get_num_tx_blocks(&self) -> Result<String> {
+        self.log("GetNumTxBlocks", self.inner.get_num_tx_blocks())
+            .await
+    }
+
+    async fn get_num_ds_blocks(&self) -> Result<String> {
+        self.log("GetNumDSBlocks", self.inner.get_num_ds_blocks())
+            .await
+    }
+
+    async fn get_num_transactions(&self) -> Result<String> {
+        self.log("GetNumTransactions", self.inner.get_num_transactions())
+            .await
+    }
+
+    async fn get_transaction_rate(&self) -> Result<f64> {
+        self.log("GetTransactionRate", self.inner.get_transaction_rate())
+            .await
+    }
+
+    async fn get_tx_block_rate(&self) -> Result<f64> {
+        self.log("GetTxBlockRate", self.inner.get_tx_block_rate())
+            .await
+    }
+
+    async fn get_ds_block_rate(&self) -> Result<f64> {
+        self.log("GetDSBlockRate", self.inner.get_ds_block_rate())
+            .await
+    }
+
+    async fn get_total_coin_supply(&self) -> Result<String> {
+        self.log("GetTotalCoinSupply", self.inner.get_total_coin_supply())
+            .await
+    }
+
+    async fn get_prev_difficulty(&self) -> Result<u32> {
+        self.log("GetPrevDifficulty", self.inner.get_prev_difficulty())
+            .await
+    }
+
+    async fn get_prev_ds_difficulty(&self) -> Result<u32> {
+        self.log("GetPrevDSDifficulty", self.inner.get_prev_ds_difficulty())
+            .await
+    }
+
+    async fn get_smart_contract_init(&self, contract_address: &str) -> Result<Vec<ContractValue>> {
+        self.log(
+            "GetSmartContractInit",
+            self.inner.get_smart_contract_init(contract_address),
+        )
+        .await
+    }
+
+    async fn get_smart_contract_code(&self, contract_address: &str) -> Result<SmartContractCode> {
+        self.log(
+            "GetSmartContractCode",
+            self.inner.get_smart_contract_code(contract_address),
+        )
+        .await
+    }
+
+    async fn get_smart_contract_substate(
+        &self,
+        contract_address: &str,
+        variable_name: &str,
+        indices: &[&str],
+    ) -> Result<Value> {
+        self.log(
+            "GetSmartContractSubState",
+            self.inner
+                .get_smart_contract_substate(contract_address, variable_name, indices),
+        )
+        .await
+    }
+
+    async fn get_contract_address_from_transaction_id(
+        &self,
+        transaction_id: &str,
+    ) -> Result<String> {
+        self.log(
+            "GetContractAddressFromTransactionID",
+            self.inner
+                .get_contract_address_from_transaction_id(transaction_id),
+        )
+        .await
+    }
+
+    async fn get_balance(&self, user_address: &str) -> Result<BalanceAndNonce> {
+        self.log("GetBalance", self.inner.get_balance(user_address))
+            .await
+    }
+
+    async fn batch_get_balance(
+        &self,
+        user_addresses: &[&str],
+    ) -> Result<Vec<Result<BalanceAndNonce>>> {
+        self.log(
+            "GetBalance (batch)",
+            self.inner.batch_get_balance(user_addresses),
+        )
+        .await
+    }
+
+    async fn batch_get_transaction(
+        &self,
+        transaction_hashes: &[&str],
+    ) -> Result<Vec<Result<Transaction>>> {
+        self.log(
+            "GetTransaction (batch)",
+            self.inner.batch_get_transaction(transaction_hashes),
+        )
+        .await
+    }
+
+    async fn send_transaction(&self, tx: Transaction) -> Result<CreateTransactionResponse> {
+        self.log("SendTransaction", self.inner.send_transaction(tx))
+            .await
+    }
+
+    async fn create_transaction(&self, tx: &Transaction) -> Result<CreateTransactionResponse> {
+        self.log("CreateTransaction", self.inner.create_transaction(tx))
+            .await
+    }
+}
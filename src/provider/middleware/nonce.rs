@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use super::Middleware;
+use crate::core::{CreateTransactionResponse, Transaction};
+use crate::jsonrpc::ErrorCode;
+use crate::provider::Result;
+
+/// Hands out consecutive nonces for transactions sent from `address`, so that several
+/// transactions can be sent without waiting for the previous ones to be confirmed.
+///
+/// The first nonce is fetched from the network. After a failed send the manager fetches it
+/// again, as the node may or may not have accepted the transaction.
+pub struct NonceManagerMiddleware<M> {
+    inner: M,
+    address: String,
+    next_nonce: Mutex<Option<u64>>,
+}
+
+impl<M: Middleware> NonceManagerMiddleware<M> {
+    pub fn new(inner: M, address: &str) -> Self {
+        Self {
+            inner,
+            address: address.to_string(),
+            next_nonce: Mutex::new(None),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Forgets the cached nonce, e.g. after transactions were sent around this middleware.
+    pub async fn reset(&self) {
+        *self.next_nonce.lock().await = None;
+    }
+
+    pub async fn next_nonce(&self) -> Result<u64> {
+        let mut next_nonce = self.next_nonce.lock().await;
+
+        let nonce = match *next_nonce {
+            Some(nonce) => nonce,
+            None => match self.inner.get_balance(&self.address).await {
+                Ok(balance) => balance.nonce + 1,
+                // Accounts that never received anything are unknown to the node.
+                Err(err)
+                    if err.rpc_error().map(|err| err.kind())
+                        == Some(ErrorCode::InvalidAddressOrKey) =>
+                {
+                    1
+                }
+                Err(err) => return Err(err),
+            },
+        };
+
+        *next_nonce = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    async fn fill_nonce(&self, tx: &mut Transaction) -> Result<()> {
+        if tx.nonce.is_none() {
+            tx.nonce = Some(self.next_nonce().await?.to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<M: Middleware> Middleware for NonceManagerMiddleware<M> {
+    type Inner = M;
+
+    fn inner(&self) -> Option<&M> {
+        Some(&self.inner)
+    }
+
+    async fn fill_transaction(&self, tx: &mut Transaction) -> Result<()> {
+        self.fill_nonce(tx).await?;
+        self.inner.fill_transaction(tx).await
+    }
+
+    async fn send_transaction(&self, mut tx: Transaction) -> Result<CreateTransactionResponse> {
+        self.fill_nonce(&mut tx).await?;
+
+        let result = self.inner.send_transaction(tx).await;
+        if result.is_err() {
+            self.reset().await;
+        }
+
+        result
+    }
+}
@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+
+use super::Middleware;
+use crate::account::wallet::{Wallet, WalletError};
+use crate::core::{CreateTransactionResponse, Transaction};
+use crate::provider::{ProviderError, Result};
+
+/// Signs transactions with an account of `wallet` before sending them. Uses the wallet's default
+/// account unless another one is picked with [`SignerMiddleware::with_address`].
+pub struct SignerMiddleware<M> {
+    inner: M,
+    wallet: Wallet,
+    address: Option<String>,
+}
+
+impl<M: Middleware> SignerMiddleware<M> {
+    pub fn new(inner: M, wallet: Wallet) -> Self {
+        Self {
+            inner,
+            wallet,
+            address: None,
+        }
+    }
+
+    pub fn with_address(inner: M, wallet: Wallet, address: &str) -> Self {
+        Self {
+            inner,
+            wallet,
+            address: Some(address.to_string()),
+        }
+    }
+
+    pub fn wallet(&self) -> &Wallet {
+        &self.wallet
+    }
+
+    /// Returns the address transactions are signed with.
+    pub fn address(&self) -> Result<String> {
+        match &self.address {
+            Some(address) => Ok(address.clone()),
+            None => self
+                .wallet
+                .default_account()
+                .map(|account| account.address().to_string())
+                .ok_or_else(|| ProviderError::middleware(WalletError::NoDefaultAccount)),
+        }
+    }
+}
+
+#[async_trait]
+impl<M: Middleware> Middleware for SignerMiddleware<M> {
+    type Inner = M;
+
+    fn inner(&self) -> Option<&M> {
+        Some(&self.inner)
+    }
+
+    async fn fill_transaction(&self, tx: &mut Transaction) -> Result<()> {
+        self.wallet
+            .prepare_transaction(&self.address()?, tx)
+            .map_err(ProviderError::middleware)?;
+        self.inner.fill_transaction(tx).await
+    }
+
+    async fn send_transaction(&self, mut tx: Transaction) -> Result<CreateTransactionResponse> {
+        self.fill_transaction(&mut tx).await?;
+        self.wallet
+            .sign_with(&self.address()?, &mut tx)
+            .map_err(ProviderError::middleware)?;
+
+        self.inner.create_transaction(&tx).await
+    }
+}
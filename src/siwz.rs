@@ -0,0 +1,496 @@
+//! Sign-In with Zilliqa, an off-chain login message modeled on EIP-4361
+//! (https://eips.ethereum.org/EIPS/eip-4361).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use thiserror::Error;
+
+use crate::crypto::schnorr::{sign_message, verify_message, Signature};
+use crate::keytools::{generate_random_bytes, get_address_from_public_key};
+use crate::util::{normalize_address, to_check_sum_address};
+
+const PREAMBLE: &str = " wants you to sign in with your Zilliqa account:";
+const VERSION: &str = "1";
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SiwzError {
+    #[error("invalid message: {0}")]
+    Parse(String),
+    #[error("invalid address {0}")]
+    InvalidAddress(String),
+    #[error("invalid signature")]
+    InvalidSignature,
+    #[error("public key does not match address {0}")]
+    AddressMismatch(String),
+    #[error("expected domain {expected}, got {actual}")]
+    DomainMismatch { expected: String, actual: String },
+    #[error("expected chain id {expected}, got {actual}")]
+    ChainIdMismatch { expected: u32, actual: u32 },
+    #[error("message expired at {0}")]
+    Expired(DateTime<Utc>),
+    #[error("message issued in the future at {0}")]
+    NotYetValid(DateTime<Utc>),
+    #[error("nonce {0} has already been used")]
+    NonceReused(String),
+}
+
+pub type Result<T> = std::result::Result<T, SiwzError>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiwzMessage {
+    pub domain: String,
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u32,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: Option<DateTime<Utc>>,
+    pub resources: Vec<String>,
+}
+
+impl SiwzMessage {
+    /// Creates a message issued now, with a fresh random nonce. `address` may be base16 or bech32
+    /// and is rendered as a checksum address.
+    pub fn new(domain: &str, address: &str, uri: &str, chain_id: u32) -> Result<Self> {
+        let normalized = normalize_address(address)
+            .map_err(|_| SiwzError::InvalidAddress(address.to_string()))?;
+
+        Ok(Self {
+            domain: domain.to_string(),
+            address: to_check_sum_address(&normalized),
+            statement: None,
+            uri: uri.to_string(),
+            version: VERSION.to_string(),
+            chain_id,
+            nonce: generate_nonce(),
+            issued_at: Utc::now(),
+            expiration_time: None,
+            resources: Vec::new(),
+        })
+    }
+
+    /// Signs the canonical text of the message, returning the hex encoded signature.
+    pub fn sign(
+        &self,
+        private_key: &[u8],
+    ) -> std::result::Result<String, Box<dyn std::error::Error>> {
+        Ok(sign_message(&self.to_string(), private_key)?.to_hex())
+    }
+}
+
+impl fmt::Display for SiwzMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}{}", self.domain, PREAMBLE)?;
+        writeln!(f, "{}", self.address)?;
+        writeln!(f)?;
+
+        if let Some(statement) = &self.statement {
+            writeln!(f, "{}", statement)?;
+            writeln!(f)?;
+        }
+
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Chain ID: {}", self.chain_id)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        write!(f, "Issued At: {}", format_time(&self.issued_at))?;
+
+        if let Some(expiration_time) = &self.expiration_time {
+            write!(f, "\nExpiration Time: {}", format_time(expiration_time))?;
+        }
+
+        if !self.resources.is_empty() {
+            write!(f, "\nResources:")?;
+            for resource in &self.resources {
+                write!(f, "\n- {}", resource)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for SiwzMessage {
+    type Err = SiwzError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut lines = s.split('\n').peekable();
+
+        let domain = lines
+            .next()
+            .and_then(|line| line.strip_suffix(PREAMBLE))
+            .filter(|domain| !domain.is_empty())
+            .ok_or_else(|| parse_error("missing preamble"))?;
+
+        let address = lines
+            .next()
+            .filter(|address| normalize_address(address).is_ok())
+            .ok_or_else(|| parse_error("missing or invalid address"))?;
+
+        expect_empty(lines.next())?;
+
+        let statement = match lines.peek() {
+            Some(line) if !line.starts_with("URI: ") => {
+                let statement = line.to_string();
+                lines.next();
+                expect_empty(lines.next())?;
+                Some(statement)
+            }
+            _ => None,
+        };
+
+        let uri = field(lines.next(), "URI")?;
+        let version = field(lines.next(), "Version")?;
+        if version != VERSION {
+            return Err(parse_error(&format!("unsupported version {}", version)));
+        }
+
+        let chain_id = field(lines.next(), "Chain ID")?
+            .parse()
+            .map_err(|_| parse_error("invalid chain id"))?;
+        let nonce = field(lines.next(), "Nonce")?;
+        if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(parse_error(
+                "nonce must be at least 8 alphanumeric characters",
+            ));
+        }
+
+        let issued_at = parse_time(&field(lines.next(), "Issued At")?)?;
+
+        let expiration_time = match lines.peek() {
+            Some(line) if line.starts_with("Expiration Time: ") => {
+                Some(parse_time(&field(lines.next(), "Expiration Time")?)?)
+            }
+            _ => None,
+        };
+
+        let mut resources = Vec::new();
+        if let Some(line) = lines.next() {
+            if line != "Resources:" {
+                return Err(parse_error(&format!("unexpected line {:?}", line)));
+            }
+
+            for line in lines {
+                let resource = line
+                    .strip_prefix("- ")
+                    .ok_or_else(|| parse_error(&format!("invalid resource {:?}", line)))?;
+                resources.push(resource.to_string());
+            }
+        }
+
+        Ok(Self {
+            domain: domain.to_string(),
+            address: address.to_string(),
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time,
+            resources,
+        })
+    }
+}
+
+/// Server side verification of signed messages. Nonces of successfully verified messages are
+/// remembered until the message expires, so that a signed message can not be replayed. Messages
+/// older than the maximum age are rejected even without an expiration time, which bounds how long
+/// a nonce has to be kept.
+pub struct Verifier {
+    domain: String,
+    chain_id: u32,
+    max_age: Duration,
+    used_nonces: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl Verifier {
+    pub fn new(domain: &str, chain_id: u32) -> Self {
+        Self {
+            domain: domain.to_string(),
+            chain_id,
+            max_age: Duration::days(1),
+            used_nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets how long after being issued a message is accepted, one day by default.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Verifies `message` against a hex encoded compressed public key and signature, returning
+    /// the parsed message on success.
+    pub fn verify(&self, message: &str, public_key: &str, signature: &str) -> Result<SiwzMessage> {
+        self.verify_at(message, public_key, signature, Utc::now())
+    }
+
+    pub fn verify_at(
+        &self,
+        message: &str,
+        public_key: &str,
+        signature: &str,
+        now: DateTime<Utc>,
+    ) -> Result<SiwzMessage> {
+        let parsed = message.parse::<SiwzMessage>()?;
+
+        if parsed.domain != self.domain {
+            return Err(SiwzError::DomainMismatch {
+                expected: self.domain.clone(),
+                actual: parsed.domain,
+            });
+        }
+
+        if parsed.chain_id != self.chain_id {
+            return Err(SiwzError::ChainIdMismatch {
+                expected: self.chain_id,
+                actual: parsed.chain_id,
+            });
+        }
+
+        let public_key = hex::decode(public_key.trim_start_matches("0x"))
+            .map_err(|_| SiwzError::InvalidSignature)?;
+        let address = get_address_from_public_key(&public_key)
+            .map_err(|_| SiwzError::AddressMismatch(parsed.address.clone()))?;
+        if normalize_address(&parsed.address).ok() != Some(address) {
+            return Err(SiwzError::AddressMismatch(parsed.address));
+        }
+
+        let signature = Signature::from_hex(signature).map_err(|_| SiwzError::InvalidSignature)?;
+        if !verify_message(message, &public_key, &signature) {
+            return Err(SiwzError::InvalidSignature);
+        }
+
+        if parsed.issued_at > now {
+            return Err(SiwzError::NotYetValid(parsed.issued_at));
+        }
+
+        let max_expiration_time = parsed.issued_at + self.max_age;
+        let expiration_time = parsed
+            .expiration_time
+            .map_or(max_expiration_time, |time| time.min(max_expiration_time));
+        if expiration_time <= now {
+            return Err(SiwzError::Expired(expiration_time));
+        }
+
+        let mut used_nonces = self.used_nonces.lock().unwrap();
+        used_nonces.retain(|_, expiration_time| *expiration_time > now);
+        if used_nonces.contains_key(&parsed.nonce) {
+            return Err(SiwzError::NonceReused(parsed.nonce));
+        }
+        used_nonces.insert(parsed.nonce.clone(), expiration_time);
+
+        Ok(parsed)
+    }
+}
+
+pub fn generate_nonce() -> String {
+    String::from_utf8(generate_random_bytes(16)).unwrap()
+}
+
+fn field(line: Option<&str>, name: &str) -> Result<String> {
+    line.and_then(|line| line.strip_prefix(name))
+        .and_then(|line| line.strip_prefix(": "))
+        .map(|value| value.to_string())
+        .ok_or_else(|| parse_error(&format!("missing {}", name)))
+}
+
+fn expect_empty(line: Option<&str>) -> Result<()> {
+    match line {
+        Some("") => Ok(()),
+        _ => Err(parse_error("expected an empty line")),
+    }
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn parse_time(time: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| parse_error(&format!("invalid timestamp {}", time)))
+}
+
+fn parse_error(message: &str) -> SiwzError {
+    SiwzError::Parse(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bech32::to_bech32_addr;
+    use crate::keytools::get_public_key_from_private_key;
+
+    const PRIVATE_KEY: &str = "24180e6b0c3021aedb8f5a86f75276ee6fc7ff46e67e98e716728326102e91c9";
+
+    const MESSAGE: &str = "example.com wants you to sign in with your Zilliqa account:
+0xB5C2CDd79C37209C3cB59E04B7C4062A8F5D5271
+
+Sign in to Example.
+
+URI: https://example.com/login
+Version: 1
+Chain ID: 333
+Nonce: 32891756
+Issued At: 2021-09-30T16:25:24Z
+Expiration Time: 2021-10-01T16:25:24Z
+Resources:
+- https://example.com/terms";
+
+    fn signed() -> (String, String, String) {
+        signed_with(Some(Duration::minutes(5)))
+    }
+
+    fn signed_with(expires_in: Option<Duration>) -> (String, String, String) {
+        let private_key = hex::decode(PRIVATE_KEY).unwrap();
+        let public_key = get_public_key_from_private_key(&private_key, true).unwrap();
+
+        let mut message = SiwzMessage::new(
+            "example.com",
+            "b5c2cdd79c37209c3cb59e04b7c4062a8f5d5271",
+            "https://example.com/login",
+            333,
+        )
+        .unwrap();
+        message.expiration_time = expires_in.map(|expires_in| message.issued_at + expires_in);
+
+        let signature = message.sign(&private_key).unwrap();
+        (message.to_string(), hex::encode(public_key), signature)
+    }
+
+    #[test]
+    fn test_parse_and_render() {
+        let message = MESSAGE.parse::<SiwzMessage>().unwrap();
+        assert_eq!(message.domain, "example.com");
+        assert_eq!(message.statement.as_deref(), Some("Sign in to Example."));
+        assert_eq!(message.chain_id, 333);
+        assert_eq!(message.resources, vec!["https://example.com/terms"]);
+        assert_eq!(message.to_string(), MESSAGE);
+
+        let mut minimal = message;
+        minimal.statement = None;
+        minimal.expiration_time = None;
+        minimal.resources.clear();
+        assert_eq!(minimal.to_string().parse::<SiwzMessage>().unwrap(), minimal);
+
+        assert!(MESSAGE
+            .replace("Nonce: 32891756", "Nonce: 1")
+            .parse::<SiwzMessage>()
+            .is_err());
+        assert!(MESSAGE
+            .replace("Version: 1", "Version: 2")
+            .parse::<SiwzMessage>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        let (message, public_key, signature) = signed();
+        let verifier = Verifier::new("example.com", 333);
+
+        assert!(verifier.verify(&message, &public_key, &signature).is_ok());
+        assert!(matches!(
+            verifier.verify(&message, &public_key, &signature),
+            Err(SiwzError::NonceReused(_))
+        ));
+    }
+
+    #[test]
+    fn test_new_invalid_address() {
+        let bech32 = to_bech32_addr("b5c2cdd79c37209c3cb59e04b7c4062a8f5d5271").unwrap();
+        let message =
+            SiwzMessage::new("example.com", &bech32, "https://example.com/login", 333).unwrap();
+        assert_eq!(
+            message.address,
+            to_check_sum_address("b5c2cdd79c37209c3cb59e04b7c4062a8f5d5271")
+        );
+
+        assert_eq!(
+            SiwzMessage::new("example.com", "zil1invalid", "https://example.com", 333),
+            Err(SiwzError::InvalidAddress("zil1invalid".into()))
+        );
+    }
+
+    #[test]
+    fn test_used_nonces_expire() {
+        let (message, public_key, signature) = signed();
+        let verifier = Verifier::new("example.com", 333);
+        let now = Utc::now();
+
+        verifier
+            .verify_at(&message, &public_key, &signature, now)
+            .unwrap();
+        assert_eq!(verifier.used_nonces.lock().unwrap().len(), 1);
+
+        let (message, public_key, signature) = signed_with(Some(Duration::hours(1)));
+        verifier
+            .verify_at(
+                &message,
+                &public_key,
+                &signature,
+                now + Duration::minutes(10),
+            )
+            .unwrap();
+        assert_eq!(verifier.used_nonces.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_max_age() {
+        let (message, public_key, signature) = signed_with(None);
+        let verifier = Verifier::new("example.com", 333).max_age(Duration::minutes(1));
+        assert!(matches!(
+            verifier.verify_at(
+                &message,
+                &public_key,
+                &signature,
+                Utc::now() + Duration::hours(1)
+            ),
+            Err(SiwzError::Expired(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_rejects() {
+        let (message, public_key, signature) = signed();
+        let now = Utc::now();
+
+        let verifier = Verifier::new("example.com", 333);
+        assert!(matches!(
+            verifier.verify_at(&message, &public_key, &signature, now + Duration::hours(1)),
+            Err(SiwzError::Expired(_))
+        ));
+
+        let tampered = message.replace("Chain ID: 333", "Chain ID: 1");
+        let verifier = Verifier::new("example.com", 1);
+        assert_eq!(
+            verifier.verify(&tampered, &public_key, &signature),
+            Err(SiwzError::InvalidSignature)
+        );
+
+        let other = get_public_key_from_private_key(
+            &hex::decode("af71626e38926401a6d2fd8fdf91c97f785b8fb2b867e7f8a884351e59ee9aa6")
+                .unwrap(),
+            true,
+        )
+        .unwrap();
+        let verifier = Verifier::new("example.com", 333);
+        assert!(matches!(
+            verifier.verify(&message, &hex::encode(other), &signature),
+            Err(SiwzError::AddressMismatch(_))
+        ));
+
+        let verifier = Verifier::new("other.com", 333);
+        assert!(matches!(
+            verifier.verify(&message, &public_key, &signature),
+            Err(SiwzError::DomainMismatch { .. })
+        ));
+    }
+}
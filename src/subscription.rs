@@ -0,0 +1,478 @@
+//! Subscriptions over the WebSocket API, enabled with the `ws` feature.
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use zilliqa_sdk::subscription::SubscriptionClient;
+//!
+//! # async fn run() -> zilliqa_sdk::subscription::Result<()> {
+//! let client = SubscriptionClient::connect("wss://api-ws.zilliqa.com").await?;
+//! let mut blocks = client.new_blocks();
+//! while let Some(block) = blocks.next().await {
+//!     println!("{}", block?.tx_block.header.block_num);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeSet;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::{SinkExt, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use thiserror::Error;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+use crate::core::{ContractEventLogs, NewBlock};
+use crate::jsonrpc::transport::redact_url;
+use crate::jsonrpc::{PolicyError, RetryPolicy};
+use crate::util::normalize_address;
+
+#[derive(Error, Debug)]
+pub enum SubscriptionError {
+    #[error(transparent)]
+    WebSocket(Box<tungstenite::Error>),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+    #[error("invalid address {0}")]
+    InvalidAddress(String),
+    #[error(transparent)]
+    Policy(#[from] PolicyError),
+}
+
+impl From<tungstenite::Error> for SubscriptionError {
+    fn from(err: tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, SubscriptionError>;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+enum Command {
+    Subscribe(Subscriber),
+    /// Sent when a [`Subscription`] is dropped.
+    Prune,
+}
+
+enum Filter {
+    NewBlock,
+    EventLog(BTreeSet<String>),
+}
+
+struct Subscriber {
+    filter: Filter,
+    sender: mpsc::UnboundedSender<Value>,
+}
+
+/// A connection to the WebSocket API of a node, shared by any number of [`Subscription`]s.
+///
+/// The connection is kept by a background task. When it drops, the task reconnects and
+/// subscribes again to everything that is still being listened to. Notifications sent by the
+/// node in the meantime are lost.
+pub struct SubscriptionClient {
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+impl SubscriptionClient {
+    /// Connects to `url`, reconnecting without limit when the connection drops.
+    pub async fn connect(url: &str) -> Result<Self> {
+        let policy = RetryPolicy {
+            max_retries: u32::MAX,
+            ..RetryPolicy::default()
+        };
+
+        Self::connect_with_policy(url, policy).await
+    }
+
+    /// Connects to `url`. When the connection drops, reconnecting is attempted up to
+    /// `policy.max_retries` times in a row, waiting `policy.backoff` in between, after which all
+    /// subscriptions end.
+    pub async fn connect_with_policy(url: &str, policy: RetryPolicy) -> Result<Self> {
+        policy.validate()?;
+        let (socket, _) = connect_async(url).await?;
+        let (commands, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run(url.to_string(), policy, socket, receiver));
+
+        Ok(Self { commands })
+    }
+
+    /// Streams every new TxBlock along with the hashes of its transactions.
+    pub fn new_blocks(&self) -> Subscription<NewBlock> {
+        self.subscribe(Filter::NewBlock)
+    }
+
+    /// Streams the events emitted by the contracts at `addresses`, one item per contract and
+    /// block.
+    pub fn event_logs(&self, addresses: &[&str]) -> Result<Subscription<ContractEventLogs>> {
+        let addresses = addresses
+            .iter()
+            .map(|address| {
+                normalize_address(address)
+                    .map_err(|_| SubscriptionError::InvalidAddress(address.to_string()))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(self.subscribe(Filter::EventLog(addresses)))
+    }
+
+    fn subscribe<T>(&self, filter: Filter) -> Subscription<T> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let _ = self
+            .commands
+            .send(Command::Subscribe(Subscriber { filter, sender }));
+
+        Subscription {
+            receiver,
+            commands: self.commands.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// A stream of notifications. Dropping it unsubscribes.
+pub struct Subscription<T> {
+    receiver: mpsc::UnboundedReceiver<Value>,
+    commands: mpsc::UnboundedSender<Command>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> Stream for Subscription<T> {
+    type Item = Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver
+            .poll_recv(cx)
+            .map(|value| value.map(|value| Ok(serde_json::from_value(value)?)))
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        self.receiver.close();
+        let _ = self.commands.send(Command::Prune);
+    }
+}
+
+/// What the node has to be subscribed to: new blocks, and the union of all event log filters.
+#[derive(Default, PartialEq)]
+struct Wanted {
+    new_blocks: bool,
+    addresses: BTreeSet<String>,
+}
+
+impl Wanted {
+    fn of(subscribers: &[Subscriber]) -> Self {
+        let mut wanted = Self::default();
+        for subscriber in subscribers {
+            match &subscriber.filter {
+                Filter::NewBlock => wanted.new_blocks = true,
+                Filter::EventLog(addresses) => wanted.addresses.extend(addresses.iter().cloned()),
+            }
+        }
+
+        wanted
+    }
+
+    /// Returns the messages taking the node from being subscribed to `self` to `next`. A new
+    /// `EventLog` query replaces the addresses of the previous one.
+    fn queries(&self, next: &Wanted) -> Vec<Value> {
+        let mut queries = Vec::new();
+
+        if next.new_blocks && !self.new_blocks {
+            queries.push(json!({"query": "NewBlock"}));
+        } else if !next.new_blocks && self.new_blocks {
+            queries.push(json!({"query": "Unsubscribe", "type": "NewBlock"}));
+        }
+
+        if next.addresses != self.addresses {
+            if next.addresses.is_empty() {
+                queries.push(json!({"query": "Unsubscribe", "type": "EventLog"}));
+            } else {
+                let addresses: Vec<_> = next
+                    .addresses
+                    .iter()
+                    .map(|address| format!("0x{}", address))
+                    .collect();
+                queries.push(json!({"query": "EventLog", "addresses": addresses}));
+            }
+        }
+
+        queries
+    }
+}
+
+async fn run(
+    url: String,
+    policy: RetryPolicy,
+    mut socket: Socket,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+) {
+    let mut subscribers = Vec::new();
+    let endpoint = redact_url(&url).unwrap_or_default();
+
+    loop {
+        if !serve(&mut socket, &mut subscribers, &mut commands).await {
+            return;
+        }
+
+        let mut failures = 0;
+        socket = loop {
+            if failures >= policy.max_retries {
+                tracing::warn!(%endpoint, failures, "giving up reconnecting");
+                return;
+            }
+            tokio::time::sleep(policy.backoff(failures)).await;
+
+            match connect_async(&url).await {
+                Ok((socket, _)) => break socket,
+                Err(err) => {
+                    let err = redact_error(&err);
+                    tracing::debug!(%endpoint, %err, "reconnecting failed");
+                    failures += 1;
+                }
+            }
+        };
+    }
+}
+
+/// Describes a connection error without the URL some of them quote, credentials included.
+fn redact_error(err: &tungstenite::Error) -> String {
+    match err {
+        tungstenite::Error::Url(tungstenite::error::UrlError::UnableToConnect(_)) => {
+            "unable to connect".to_string()
+        }
+        err => err.to_string(),
+    }
+}
+
+/// Handles one connection. Returns whether to reconnect, which is not the case once the client
+/// and all subscriptions are gone.
+async fn serve(
+    socket: &mut Socket,
+    subscribers: &mut Vec<Subscriber>,
+    commands: &mut mpsc::UnboundedReceiver<Command>,
+) -> bool {
+    if send(socket, Wanted::default().queries(&Wanted::of(subscribers)))
+        .await
+        .is_err()
+    {
+        return true;
+    }
+
+    loop {
+        tokio::select! {
+            command = commands.recv() => {
+                let before = Wanted::of(subscribers);
+                match command {
+                    Some(Command::Subscribe(subscriber)) => subscribers.push(subscriber),
+                    Some(Command::Prune) => {
+                        subscribers.retain(|subscriber| !subscriber.sender.is_closed())
+                    }
+                    None => {
+                        let _ = socket.close(None).await;
+                        return false;
+                    }
+                }
+
+                if send(socket, before.queries(&Wanted::of(subscribers))).await.is_err() {
+                    return true;
+                }
+            }
+            message = socket.next() => match message {
+                Some(Ok(Message::Text(text))) => dispatch(subscribers, &text),
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                    tracing::debug!("subscription connection dropped");
+                    return true;
+                }
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+async fn send(socket: &mut Socket, queries: Vec<Value>) -> tungstenite::Result<()> {
+    for query in queries {
+        socket.send(Message::Text(query.to_string())).await?;
+    }
+
+    Ok(())
+}
+
+fn dispatch(subscribers: &[Subscriber], text: &str) {
+    let message: Value = match serde_json::from_str(text) {
+        Ok(message) => message,
+        Err(_) => return,
+    };
+    if message["type"] != "Notification" {
+        return;
+    }
+
+    for notification in message["values"].as_array().into_iter().flatten() {
+        let value = &notification["value"];
+
+        for subscriber in subscribers {
+            match (&subscriber.filter, notification["query"].as_str()) {
+                (Filter::NewBlock, Some("NewBlock")) => {
+                    let _ = subscriber.sender.send(value.clone());
+                }
+                (Filter::EventLog(addresses), Some("EventLog")) => {
+                    for logs in value.as_array().into_iter().flatten() {
+                        let address = logs["address"].as_str().unwrap_or_default();
+                        if normalize_address(address).is_ok_and(|a| addresses.contains(&a)) {
+                            let _ = subscriber.sender.send(logs.clone());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    use super::*;
+    use crate::mock::fixtures;
+
+    const OTHER_ADDRESS: &str = "4baf5fada8e5db92c3d3242618c5b47133ae003c";
+
+    async fn listen() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        (listener, url)
+    }
+
+    async fn accept(listener: &TcpListener) -> WebSocketStream<TcpStream> {
+        let (stream, _) = listener.accept().await.unwrap();
+        accept_async(stream).await.unwrap()
+    }
+
+    async fn receive(socket: &mut WebSocketStream<TcpStream>) -> Value {
+        loop {
+            if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    fn notification() -> Message {
+        let event = |address: &str| {
+            json!({
+                "address": format!("0x{}", address),
+                "event_logs": [{"_eventname": "Minted", "params": []}]
+            })
+        };
+
+        let message = json!({
+            "type": "Notification",
+            "values": [
+                {
+                    "query": "NewBlock",
+                    "value": {
+                        "TxBlock": fixtures::tx_block(),
+                        "TxHashes": [[fixtures::TRANSACTION_HASH]]
+                    }
+                },
+                {
+                    "query": "EventLog",
+                    "value": [event(fixtures::CONTRACT_ADDRESS), event(OTHER_ADDRESS)]
+                }
+            ]
+        });
+
+        Message::Text(message.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions() {
+        let (listener, url) = listen().await;
+        let (client, mut socket) =
+            tokio::join!(SubscriptionClient::connect(&url), accept(&listener));
+        let client = client.unwrap();
+
+        let mut blocks = client.new_blocks();
+        let mut events = client
+            .event_logs(&[&fixtures::CONTRACT_ADDRESS.to_uppercase()])
+            .unwrap();
+        assert_eq!(receive(&mut socket).await, json!({"query": "NewBlock"}));
+        assert_eq!(
+            receive(&mut socket).await,
+            json!({
+                "query": "EventLog",
+                "addresses": [format!("0x{}", fixtures::CONTRACT_ADDRESS)]
+            })
+        );
+
+        socket.send(notification()).await.unwrap();
+        socket.send(notification()).await.unwrap();
+
+        let block = blocks.next().await.unwrap().unwrap();
+        assert_eq!(block.tx_block.header.block_num, fixtures::TX_BLOCK_NUMBER);
+        assert_eq!(block.tx_hashes, vec![vec![fixtures::TRANSACTION_HASH]]);
+
+        // Only the subscribed contract is passed on.
+        for _ in 0..2 {
+            let logs = events.next().await.unwrap().unwrap();
+            assert_eq!(logs.address, format!("0x{}", fixtures::CONTRACT_ADDRESS));
+            assert_eq!(logs.event_logs[0].event_name, "Minted");
+        }
+
+        drop(events);
+        assert_eq!(
+            receive(&mut socket).await,
+            json!({"query": "Unsubscribe", "type": "EventLog"})
+        );
+        assert!(client.event_logs(&["not an address"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reconnect() {
+        let capture = crate::jsonrpc::telemetry::tests::Capture::default();
+        let _guard = tracing::subscriber::set_default(capture.clone());
+
+        let (listener, url) = listen().await;
+        let url = url.replace("ws://", "ws://user:secret@") + "/?apikey=secret";
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(10),
+            ..RetryPolicy::default()
+        };
+        let (client, mut socket) = tokio::join!(
+            SubscriptionClient::connect_with_policy(&url, policy),
+            accept(&listener)
+        );
+        let mut blocks = client.unwrap().new_blocks();
+
+        assert_eq!(receive(&mut socket).await, json!({"query": "NewBlock"}));
+        socket.close(None).await.unwrap();
+
+        // Subscriptions are made again on the new connection.
+        let mut socket = accept(&listener).await;
+        assert_eq!(receive(&mut socket).await, json!({"query": "NewBlock"}));
+        socket.send(notification()).await.unwrap();
+
+        let block = blocks.next().await.unwrap().unwrap();
+        assert_eq!(block.tx_block.header.block_num, fixtures::TX_BLOCK_NUMBER);
+
+        // Streams end once reconnecting is given up.
+        drop(socket);
+        drop(listener);
+        assert!(blocks.next().await.is_none());
+
+        // Credentials are kept out of the logs.
+        let fields = capture.0.lock().unwrap();
+        assert!(fields.iter().any(|field| field.starts_with("endpoint=")));
+        assert!(!fields.iter().any(|field| field.contains("secret")));
+    }
+}
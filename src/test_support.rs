@@ -0,0 +1,27 @@
+//! Values shared by the unit tests of several modules.
+
+use crate::core::Transaction;
+use crate::util::pack;
+
+pub const RECIPIENT: &str = "4BAF5faDA8e5Db92C3d3242618c5B47133AE003C";
+pub const SENDER_PUBLIC_KEY: &str =
+    "0246E7178DC8253201101E18FD6F6EB9972451D121FC57AA2A06DD5C111E58DC6A";
+
+/// An unsigned payment of 10000000 Qa to [`RECIPIENT`] on chain 333, with nonce 1.
+pub fn payment() -> Transaction {
+    Transaction {
+        version: pack(333, 1).to_string(),
+        sender_pub_key: SENDER_PUBLIC_KEY.into(),
+        to_addr: RECIPIENT.into(),
+        amount: "10000000".into(),
+        gas_price: "2000000000".into(),
+        gas_limit: "50".into(),
+        code: None,
+        data: None,
+        priority: false,
+        id: None,
+        nonce: Some("1".into()),
+        receipt: None,
+        signature: None,
+    }
+}
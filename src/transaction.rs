@@ -0,0 +1,133 @@
+use std::error::Error;
+
+use crate::core::Transaction;
+use crate::crypto::schnorr::sign;
+use crate::keytools::get_public_key_from_private_key;
+use crate::util::normalize_address;
+
+const WIRE_TYPE_VARINT: u32 = 0;
+const WIRE_TYPE_LENGTH_DELIMITED: u32 = 2;
+
+/// Encodes the signed part of a transaction as a `ProtoTransactionCoreInfo` message, see
+/// https://github.com/Zilliqa/Zilliqa/blob/master/src/libMessage/ZilliqaMessage.proto.
+pub fn encode_transaction_proto(tx: &Transaction) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut buffer = Vec::new();
+
+    write_varint_field(&mut buffer, 1, tx.version.parse::<u32>()?.into());
+    write_varint_field(
+        &mut buffer,
+        2,
+        tx.nonce.as_deref().unwrap_or("0").parse::<u64>()?,
+    );
+    write_bytes_field(
+        &mut buffer,
+        3,
+        &hex::decode(normalize_address(&tx.to_addr)?)?,
+    );
+    write_bytes_field(
+        &mut buffer,
+        4,
+        &byte_array(&hex::decode(tx.sender_pub_key.trim_start_matches("0x"))?),
+    );
+    write_bytes_field(&mut buffer, 5, &byte_array(&uint128(&tx.amount)?));
+    write_bytes_field(&mut buffer, 6, &byte_array(&uint128(&tx.gas_price)?));
+    write_varint_field(&mut buffer, 7, tx.gas_limit.parse()?);
+
+    if let Some(code) = tx.code.as_ref().filter(|code| !code.is_empty()) {
+        write_bytes_field(&mut buffer, 8, code.as_bytes());
+    }
+
+    if let Some(data) = tx.data.as_ref().filter(|data| !data.is_empty()) {
+        write_bytes_field(&mut buffer, 9, data.as_bytes());
+    }
+
+    Ok(buffer)
+}
+
+/// Fills in `sender_pub_key` and `signature` of `tx` using `private_key`.
+pub fn sign_transaction(tx: &mut Transaction, private_key: &[u8]) -> Result<(), Box<dyn Error>> {
+    let public_key = get_public_key_from_private_key(private_key, true)?;
+    tx.sender_pub_key = hex::encode(public_key);
+
+    let signature = sign(&encode_transaction_proto(tx)?, private_key)?;
+    tx.signature = Some(signature.to_hex());
+
+    Ok(())
+}
+
+fn byte_array(data: &[u8]) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    write_bytes_field(&mut buffer, 1, data);
+    buffer
+}
+
+fn uint128(value: &str) -> Result<[u8; 16], Box<dyn Error>> {
+    Ok(value.parse::<u128>()?.to_be_bytes())
+}
+
+fn write_varint_field(buffer: &mut Vec<u8>, field: u32, value: u64) {
+    write_varint(buffer, (field << 3 | WIRE_TYPE_VARINT).into());
+    write_varint(buffer, value);
+}
+
+fn write_bytes_field(buffer: &mut Vec<u8>, field: u32, data: &[u8]) {
+    write_varint(buffer, (field << 3 | WIRE_TYPE_LENGTH_DELIMITED).into());
+    write_varint(buffer, data.len() as u64);
+    buffer.extend_from_slice(data);
+}
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::schnorr::{verify, Signature};
+    use crate::test_support::payment;
+
+    #[test]
+    fn test_encode_transaction_proto() {
+        let encoded = hex::encode(encode_transaction_proto(&payment()).unwrap());
+
+        assert_eq!(
+            encoded,
+            [
+                "088180b40a",
+                "1001",
+                "1a144baf5fada8e5db92c3d3242618c5b47133ae003c",
+                "2223",
+                "0a210246e7178dc8253201101e18fd6f6eb9972451d121fc57aa2a06dd5c111e58dc6a",
+                "2a12",
+                "0a1000000000000000000000000000989680",
+                "3212",
+                "0a1000000000000000000000000077359400",
+                "3832",
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_sign_transaction() {
+        let private_key =
+            hex::decode("e19d05c5452598e24caad4a0d85a49146f7be089515c905ae6a19e8a578a6930")
+                .unwrap();
+        let mut tx = payment();
+        tx.data = Some("{\"_tag\":\"Transfer\",\"params\":[]}".into());
+
+        sign_transaction(&mut tx, &private_key).unwrap();
+
+        let public_key = hex::decode(&tx.sender_pub_key).unwrap();
+        let signature = Signature::from_hex(tx.signature.as_ref().unwrap()).unwrap();
+        assert!(verify(
+            &encode_transaction_proto(&tx).unwrap(),
+            &public_key,
+            &signature
+        ));
+    }
+}
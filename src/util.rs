@@ -2,8 +2,11 @@ use hmac::{Hmac, Mac, NewMac};
 use num_bigint::{BigInt, Sign, ToBigInt};
 use sha2::{Digest, Sha256};
 use std::cmp::Ordering;
+use std::error::Error;
 use std::ops::BitAnd;
 
+use crate::bech32::from_bech32_addr;
+
 type HmacSha256 = Hmac<Sha256>;
 
 pub fn pack(a: u32, b: u32) -> u32 {
@@ -47,6 +50,21 @@ pub fn to_check_sum_address(address: &str) -> String {
     ret
 }
 
+/// Converts a bech32, checksum or plain hex address into lowercase hex without the `0x` prefix.
+pub fn normalize_address(address: &str) -> Result<String, Box<dyn Error>> {
+    let address = if address.starts_with("zil1") {
+        from_bech32_addr(address)?
+    } else {
+        address.trim_start_matches("0x").to_lowercase()
+    };
+
+    if address.len() != 40 || hex::decode(&address).is_err() {
+        return Err(format!("invalid address {}", address).into());
+    }
+
+    Ok(address)
+}
+
 pub fn generate_mac(derived_key: &[u8], cipher_text: &[u8], iv: &[u8]) -> Vec<u8> {
     let mut buffer = Vec::new();
     buffer.extend_from_slice(&derived_key[16..]);
@@ -113,6 +131,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_normalize_address() {
+        let expected = "4baf5fada8e5db92c3d3242618c5b47133ae003c";
+        for address in [
+            "zil1fwh4ltdguhde9s7nysnp33d5wye6uqpugufkz7",
+            "0x4BAF5faDA8e5Db92C3d3242618c5B47133AE003C",
+            "4BAF5FADA8E5DB92C3D3242618C5B47133AE003C",
+        ] {
+            assert_eq!(normalize_address(address).unwrap(), expected);
+        }
+
+        assert!(normalize_address("0x1234").is_err());
+        assert!(normalize_address("zil1invalid").is_err());
+    }
+
     #[test]
     fn test_generate_mac() {
         let result = generate_mac(